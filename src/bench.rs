@@ -0,0 +1,158 @@
+// CRATES
+
+use crate::player::MAX_PLAYERS;
+use crate::rank_game::Engine;
+use crate::rules::Rules;
+use crate::seed::ShuffleVersion;
+use crate::{Deal, DealMethod, DeckSpec, First, Game, GameResult};
+use std::time::{Duration, Instant};
+
+// STRUCTS + METHODS
+
+#[derive(Debug, Clone)]
+pub struct BenchConfig {
+    pub rules: Rules,
+    pub deck: DeckSpec,
+    pub players: usize,
+    pub dealing: DealMethod,
+    pub first: First,
+    pub shuffle: ShuffleVersion,
+    pub games: u64,
+    /// Game `i` is dealt from `seed + i`, as in a batch run from `seed`.
+    pub seed: u64,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        BenchConfig {
+            rules: Rules::default(),
+            deck: DeckSpec::default(),
+            players: 2,
+            dealing: DealMethod::classic(2),
+            first: First::default(),
+            shuffle: ShuffleVersion::default(),
+            games: 100_000,
+            seed: 0,
+        }
+    }
+}
+
+/// How long one engine took to play every deal of a benchmark.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Timing {
+    pub games: u64,
+    pub cards: u64,
+    pub elapsed: Duration,
+}
+
+impl Timing {
+    pub fn games_per_second(&self) -> f64 {
+        per_second(self.games, self.elapsed)
+    }
+
+    pub fn cards_per_second(&self) -> f64 {
+        per_second(self.cards, self.elapsed)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BenchReport {
+    /// The rank-only [`Engine`].
+    pub engine: Timing,
+    /// [`Game`], moving whole cards.
+    pub reference: Timing,
+    /// Seeds of the deals the two engines disagree on.
+    pub mismatches: Vec<u64>,
+}
+
+impl BenchReport {
+    /// How many times faster the rank-only engine plays cards.
+    pub fn speedup(&self) -> f64 {
+        let reference = self.reference.cards_per_second();
+        if reference == 0.0 {
+            return 0.0;
+        }
+        self.engine.cards_per_second() / reference
+    }
+}
+
+// HELPER FUNCTIONS
+
+/// Deal `config.games` seeded games, then time the rank-only engine and the
+/// reference engine over them on this thread, checking every result
+/// against the other, infinite games included. Dealing is left out of both
+/// timings.
+pub fn run(config: &BenchConfig) -> BenchReport {
+    let cards = config.deck.cards();
+    let mut deals = Vec::with_capacity(config.games as usize);
+    let mut decks = Vec::with_capacity(config.games as usize);
+    for game_idx in 0..config.games {
+        let seed = config.seed.wrapping_add(game_idx);
+        let deck = config.shuffle.shuffle(seed, cards.clone());
+        let hands = config.dealing.deal(&deck, config.players);
+        let first = config
+            .first
+            .pick(game_idx, seed, config.shuffle, config.players);
+        deals.push((Deal::from_cards(&hands, &config.rules), first));
+        decks.push(hands);
+    }
+
+    let mut engine = Engine::new();
+    let started = Instant::now();
+    let fast = deals
+        .iter()
+        .map(|(deal, first)| {
+            let mut hands: [&[u8]; MAX_PLAYERS] = [&[]; MAX_PLAYERS];
+            for (slot, hand) in hands.iter_mut().zip(deal.hands()) {
+                *slot = hand;
+            }
+            engine.play(&hands[..deal.hands().len()], &config.rules, *first)
+        })
+        .collect::<Vec<_>>();
+    let engine = timing(&fast, started.elapsed());
+
+    let started = Instant::now();
+    let reference = decks
+        .into_iter()
+        .zip(&deals)
+        .map(|(hands, &(_, first))| {
+            Game::new(hands)
+                .with_rules(config.rules)
+                .with_first(first)
+                .run()
+        })
+        .collect::<Vec<_>>();
+    let reference_timing = timing(&reference, started.elapsed());
+
+    let mismatches = (0..config.games)
+        .zip(fast.iter().zip(&reference))
+        .filter(|(_, (fast, reference))| fast != reference)
+        .map(|(game_idx, _)| config.seed.wrapping_add(game_idx))
+        .collect();
+
+    BenchReport {
+        engine,
+        reference: reference_timing,
+        mismatches,
+    }
+}
+
+fn timing(results: &[GameResult], elapsed: Duration) -> Timing {
+    Timing {
+        games: results.len() as u64,
+        cards: results
+            .iter()
+            .map(|result| result.cards_played as u64)
+            .sum(),
+        elapsed,
+    }
+}
+
+fn per_second(count: u64, elapsed: Duration) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds > 0.0 {
+        count as f64 / seconds
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,308 @@
+// CRATES
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+// CONSTANTS
+
+/// How a joker's rank is written; a penalty joker adds its value, e.g. `*2`.
+const JOKER: char = '*';
+
+// STRUCTS + METHODS
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rank {
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+    Ace,
+    /// A joker, carrying the number of cards it demands as a penalty card
+    /// (zero for a joker that plays like a pip card).
+    Joker(u8),
+}
+
+impl Rank {
+    pub const ALL: [Rank; 13] = [
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+        Rank::Ace,
+    ];
+
+    /// The highest penalty a joker may carry, so it still fits one digit.
+    pub const MAX_JOKER_PENALTY: u8 = 9;
+
+    pub fn penalty_value(&self) -> Option<u8> {
+        match self {
+            Rank::Jack => Some(1),
+            Rank::Queen => Some(2),
+            Rank::King => Some(3),
+            Rank::Ace => Some(4),
+            Rank::Joker(penalty) if *penalty > 0 => Some(*penalty),
+            _ => None,
+        }
+    }
+
+    /// A small number unique to each rank, with jokers after the ace.
+    pub fn index(&self) -> u8 {
+        match self {
+            Rank::Joker(penalty) => Rank::ALL.len() as u8 + penalty,
+            rank => Rank::ALL.iter().position(|r| r == rank).unwrap() as u8,
+        }
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Rank::Joker(penalty) = self {
+            return match penalty {
+                0 => write!(f, "{}", JOKER),
+                _ => write!(f, "{}{}", JOKER, penalty),
+            };
+        }
+
+        write!(
+            f,
+            "{}",
+            match self {
+                Rank::Two => "2",
+                Rank::Three => "3",
+                Rank::Four => "4",
+                Rank::Five => "5",
+                Rank::Six => "6",
+                Rank::Seven => "7",
+                Rank::Eight => "8",
+                Rank::Nine => "9",
+                Rank::Ten => "10",
+                Rank::Jack => "J",
+                Rank::Queen => "Q",
+                Rank::King => "K",
+                Rank::Ace => "A",
+                Rank::Joker(_) => unreachable!(),
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Suit {
+    Spade,
+    Heart,
+    Club,
+    Diamond,
+}
+
+impl Suit {
+    pub const ALL: [Suit; 4] = [Suit::Spade, Suit::Heart, Suit::Club, Suit::Diamond];
+
+    /// The other ways a suit may be written: the solid glyph and the
+    /// initial.
+    fn aliases(&self) -> [char; 2] {
+        match self {
+            Suit::Spade => ['♠', 'S'],
+            Suit::Heart => ['♥', 'H'],
+            Suit::Club => ['♣', 'C'],
+            Suit::Diamond => ['♦', 'D'],
+        }
+    }
+}
+
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Suit::Spade => "♤",
+                Suit::Heart => "♡",
+                Suit::Club => "♧",
+                Suit::Diamond => "♢",
+            }
+        )
+    }
+}
+
+/// A playing card. Jokers have no real suit; they are dealt as alternating
+/// black (♤) and red (♡) jokers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Card {
+    pub rank: Rank,
+    pub suit: Suit,
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.rank, self.suit)
+    }
+}
+
+impl FromStr for Rank {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(penalty) = s.strip_prefix(JOKER) {
+            let penalty = match penalty {
+                "" => 0,
+                _ => penalty
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|&p| p <= Rank::MAX_JOKER_PENALTY)
+                    .ok_or_else(|| format!("invalid joker penalty '{}'", penalty))?,
+            };
+            return Ok(Rank::Joker(penalty));
+        }
+
+        // Tens are also written `T`, so every rank fits one character.
+        if s.eq_ignore_ascii_case("T") {
+            return Ok(Rank::Ten);
+        }
+        Rank::ALL
+            .into_iter()
+            .find(|rank| rank.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown rank '{}'", s))
+    }
+}
+
+impl FromStr for Suit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Suit::ALL
+            .into_iter()
+            .find(|suit| {
+                suit.to_string() == s
+                    || suit
+                        .aliases()
+                        .iter()
+                        .any(|alias| s.eq_ignore_ascii_case(alias.encode_utf8(&mut [0; 4])))
+            })
+            .ok_or_else(|| format!("unknown suit '{}'", s))
+    }
+}
+
+impl FromStr for Card {
+    type Err = String;
+
+    /// Parse a card the way it displays, or written in letters: tens may be
+    /// `T` and suits may be `S`, `H`, `C` and `D` or the solid glyphs, so
+    /// `10♤`, `TS` and `10♠` are the same card.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let suit_start = s
+            .char_indices()
+            .last()
+            .map(|(i, _)| i)
+            .ok_or_else(|| "empty card".to_string())?;
+        let (rank, suit) = s.split_at(suit_start);
+
+        Ok(Card {
+            rank: rank
+                .parse()
+                .map_err(|err| format!("{} in card '{}'", err, s))?,
+            suit: suit
+                .parse()
+                .map_err(|err| format!("{} in card '{}'", err, s))?,
+        })
+    }
+}
+
+impl Serialize for Card {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Card {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// HELPER FUNCTIONS
+
+/// Write `cards` the way games print them, joined by `separator`.
+pub fn print_cards(cards: &[Card], separator: &str) -> String {
+    cards
+        .iter()
+        .map(|card| card.to_string())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+pub fn create_deck() -> Vec<Card> {
+    let mut deck = Vec::with_capacity(52);
+    for &suit in &Suit::ALL {
+        for &rank in &Rank::ALL {
+            deck.push(Card { rank, suit });
+        }
+    }
+    deck
+}
+
+/// Deal one card at a time to each of `players` in turn, starting with the
+/// first player.
+pub fn deal_round_robin(deck: &[Card], players: usize) -> Vec<VecDeque<Card>> {
+    let mut hands = vec![VecDeque::new(); players];
+    for (i, &card) in deck.iter().enumerate() {
+        hands[i % players].push_back(card);
+    }
+    hands
+}
+
+pub fn split_deck(deck: &[Card]) -> [VecDeque<Card>; 2] {
+    let split_point = deck.len() / 2;
+    [
+        deck[..split_point].iter().copied().collect(),
+        deck[split_point..].iter().copied().collect(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cards_in_any_notation() {
+        let ten = Card {
+            rank: Rank::Ten,
+            suit: Suit::Spade,
+        };
+        for written in ["10♤", "10♠", "TS", "ts", "10s"] {
+            assert_eq!(written.parse::<Card>(), Ok(ten), "{}", written);
+        }
+        assert_eq!(
+            "*3♡".parse::<Card>().map(|card| card.rank),
+            Ok(Rank::Joker(3))
+        );
+        for written in ["", "S", "1S", "10X", "QQH", "*10S"] {
+            assert!(written.parse::<Card>().is_err(), "{}", written);
+        }
+    }
+
+    #[test]
+    fn reads_back_every_card_it_prints() {
+        for card in create_deck() {
+            assert_eq!(card.to_string().parse::<Card>(), Ok(card));
+        }
+    }
+}
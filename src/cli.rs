@@ -0,0 +1,757 @@
+// CRATES
+
+use beggar_my_neighbour::index::{DealIndex, DealSpace};
+use beggar_my_neighbour::player::{First, MAX_PLAYERS, MIN_PLAYERS};
+use beggar_my_neighbour::ratscrew::{Agent, Reaction};
+use beggar_my_neighbour::rules::{Leader, OutOfCards, Rules, SlapRules};
+use beggar_my_neighbour::search::SearchConfig;
+use beggar_my_neighbour::seed::{self, ShuffleVersion};
+use beggar_my_neighbour::transcript::Format;
+use beggar_my_neighbour::{
+    store, Deal, DealMethod, DeckSpec, Equivalence, Hands, Pack, Rank, Suit,
+};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::thread;
+
+// CONSTANTS
+
+const COMMENT: char = '#';
+
+pub const USAGE: &str = "\
+USAGE:
+    beggar_my_neighbour [OPTIONS]
+    beggar_my_neighbour replay <id|seed> [OPTIONS]
+    beggar_my_neighbour play [OPTIONS]
+    beggar_my_neighbour view [<id|seed>] [OPTIONS]
+    beggar_my_neighbour search [OPTIONS]
+    beggar_my_neighbour experiment [OPTIONS]
+    beggar_my_neighbour bench [OPTIONS]
+
+COMMANDS:
+    replay <id|seed>     Re-run a saved game, looked up by record id first
+                         and then by seed
+    play                 Play against the computer in the terminal: space
+                         lays your next card, s slaps the pile in Ratscrew
+                         games and q quits
+    view [<id|seed>]     Step through a saved game, or the game from --seed
+                         or --deal, one card at a time: arrows move a card,
+                         p/P and t/T jump between penalty phases and tricks
+    search               Hunt for the longest rank-only deals on every core
+                         with random restarts and simulated annealing
+    experiment           Play many seeded games on every core and report
+                         game lengths, how often the first player wins and
+                         how that depends on the aces in their hand
+    bench                Time the rank-only engine against the reference
+                         engine on the same seeded deals, on one thread,
+                         and check they agree
+
+OPTIONS:
+    --seed <u64>         Deal from this seed
+    --seed-hex <hex>     Deal from a seed written in hexadecimal
+    --seed-text <text>   Deal from any string, hashed to a seed
+    --shuffle <version>  How seeds shuffle the deck: v2 (ChaCha8 and
+                         Fisher-Yates, fixed for good) or v1 (the rand
+                         crate's StdRng, as games were dealt before)
+                         [default: v2]
+    --players <n>        Players at the table, 2 to 8; more than two are
+                         dealt one card at a time [default: 2]
+    --deal-method <m>    How to deal the shuffled deck: alternate (a card at
+                         a time), split (a run of cards each), packets:<n>
+                         (n cards at a time) or handicap:<n> (alternately,
+                         with the first player n cards short)
+                         [default: split for two players, else alternate]
+    --first <who>        Who leads the first trick: a player number, random
+                         (drawn from the seed) or alternate (each player in
+                         turn, game by game) [default: 1]
+    --deal <deal>        Play a fixed deal instead of a shuffled deck,
+                         rank-only as hands of -, J, Q, K and A separated by
+                         '/', e.g. \"---K---Q-KQAJ-----AAJ--J--/...\", or
+                         card for card, e.g. \"10♤ QH AS ... / 2D 7C ...\",
+                         holding exactly the cards of the deck
+    --deal-file <path>   Read a --deal from a file, with # starting a comment
+    --deal-index <n>     Play the rank-only deal with this index among every
+                         deal of the deck to the players, in dictionary
+                         order; with --games, play the deals from n on
+    --deck <pack>        Deal from a standard, piquet (sevens up, 32 cards)
+                         or euchre (nines up, 24 cards) pack
+                         [default: standard]
+    --packs <n>          Shuffle n packs together [default: 1]
+    --jokers <n>         Add n jokers to the deck [default: 0]
+    --joker-penalty <n>  Cards a joker demands, 0 to 9; 0 plays it like a
+                         number card [default: 0]
+    --deck-file <path>   Deal from the cards listed in a file instead, e.g.
+                         \"10♤ J♡ *2♧ ...\", with # starting a comment
+    --events <format>    How to report each card of a single game: pretty,
+                         json (one event per line, nothing else on stdout)
+                         or none [default: pretty]
+    --output <format>    Print a transcript of the game instead: json, csv
+                         or text (PGN-style), holding the starting deck,
+                         every hand, every move and the result
+    --leader <rule>      Who leads after a penalty phase: collector (the
+                         player who took the pile, standard) or legacy (the
+                         player who opened the phase) [default: collector]
+    --tens <n>           Make tens penalty cards demanding n cards, up to 9
+    --out-of-cards <r>   When a player runs out while paying a penalty: pass
+                         the rest to the next player, or forfeit the pile
+                         to whoever laid the penalty card [default: pass]
+    --rules <path>       Read rules from a TOML file; the flags above
+                         override it
+    --ratscrew           Play Egyptian Ratscrew: simulated players slap
+                         doubles, sandwiches, top-bottoms and marriages to
+                         take the pile, and burn a card for a false slap
+    --reaction <ms,...>  Each player's mean reaction time in milliseconds,
+                         optionally with a spread, e.g. 250:40,400; one value
+                         applies to everyone [default: 300:60]
+    --games <n>          Play n games without per-card logging and report
+                         aggregate statistics; with --seed, seeds count up
+                         from the one given
+    --each-first         With --games, play every deal once with each player
+                         leading and report how the winner changes; n
+                         counts deals
+    --forever            Like --games, but never stop
+    --db <path>          Results file [default: results.jsonl]
+    --no-save            Do not append results to the results file
+    --dedup <same>       Do not save a game the results file already holds:
+                         deal (the same penalty values in every hand and
+                         the same leader) or seats (also with the table
+                         turned so another player is player one)
+    -h, --help           Print this message
+
+SEARCH OPTIONS:
+    --metric <metric>    Lengthen games by tricks or cards [default: cards]
+    --threads <n>        Worker threads [default: one per core]
+    --restarts <n>       Stop after n random restarts [default: never]
+    --iterations <n>     Card swaps tried per restart [default: 20000]
+    --temperature <t>    Starting annealing temperature; 0 for plain
+                         hill-climbing [default: 50]
+    --top <n>            Leaderboard size [default: 10]
+    --seed <u64>         Master seed for the workers [default: random]
+
+EXPERIMENT OPTIONS:
+    --games <n>          Games to play [default: 1000000]
+    --threads <n>        Worker threads [default: one per core]
+    --seed <u64>         Deal game i from seed + i [default: random]
+    --output <format>    Print the summary as json or csv instead of a
+                         report
+
+BENCH OPTIONS:
+    --games <n>          Deals to play with each engine [default: 100000]
+    --seed <u64>         Deal game i from seed + i [default: random]";
+
+// STRUCTS + METHODS
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Events {
+    #[default]
+    Pretty,
+    Json,
+    None,
+}
+
+impl FromStr for Events {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pretty" => Ok(Events::Pretty),
+            "json" => Ok(Events::Json),
+            "none" => Ok(Events::None),
+            _ => Err(format!(
+                "unknown event format '{}', expected pretty, json or none",
+                s
+            )),
+        }
+    }
+}
+
+/// A starting position given with `--deal` or `--deal-file`.
+#[derive(Debug, Clone)]
+pub enum StartingDeal {
+    Ranks(Deal),
+    Cards(Hands),
+}
+
+impl FromStr for StartingDeal {
+    type Err = String;
+
+    /// Card-for-card deals are told apart from rank-only ones by their suits,
+    /// which rank-only notation never writes.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.chars().any(|c| c.to_string().parse::<Suit>().is_ok()) {
+            return s.parse().map(StartingDeal::Cards);
+        }
+        s.parse().map(StartingDeal::Ranks)
+    }
+}
+
+#[derive(Debug, Default)]
+pub enum Command {
+    #[default]
+    Run,
+    Replay(u64),
+    Play,
+    View(Option<u64>),
+    Search,
+    Experiment,
+    Bench,
+}
+
+#[derive(Debug)]
+pub struct Args {
+    pub command: Command,
+    pub seed: Option<u64>,
+    pub shuffle: ShuffleVersion,
+    pub deal: Option<StartingDeal>,
+    /// The first deal to play, by its place among every rank-only deal.
+    pub deal_index: Option<DealIndex>,
+    pub players: usize,
+    pub deal_method: Option<DealMethod>,
+    pub first: First,
+    /// Play every deal of a batch once with each player leading.
+    pub each_first: bool,
+    pub deck: DeckSpec,
+    pub events: Events,
+    pub output: Option<Format>,
+    pub rules: Rules,
+    pub reactions: Vec<Reaction>,
+    pub games: Option<u64>,
+    pub forever: bool,
+    pub threads: usize,
+    pub search: SearchConfig,
+    pub db: PathBuf,
+    pub save: bool,
+    /// Skip saving games the results file already holds.
+    pub dedup: Option<Equivalence>,
+    pub help: bool,
+}
+
+impl Args {
+    pub fn is_batch(&self) -> bool {
+        self.forever || self.games.is_some()
+    }
+
+    /// How shuffled decks are dealt: as asked, or the classic way.
+    pub fn dealing(&self) -> DealMethod {
+        self.deal_method
+            .unwrap_or_else(|| DealMethod::classic(self.players))
+    }
+
+    /// Every rank-only deal of the deck to the players, which `--deal-index`
+    /// counts through.
+    pub fn deal_space(&self) -> DealSpace {
+        DealSpace::of_deck(&self.deck, &self.rules, self.dealing(), self.players)
+    }
+
+    /// The simulated slappers for a table of `players`, or none if the rules
+    /// have no slaps.
+    pub fn agents(&self, players: usize) -> Result<Vec<Agent>, String> {
+        if !self.rules.slaps.enabled() {
+            return Ok(Vec::new());
+        }
+
+        let reactions = match self.reactions.len() {
+            0 => vec![Reaction::default(); players],
+            1 => vec![self.reactions[0]; players],
+            n if n == players => self.reactions.clone(),
+            n => {
+                return Err(format!(
+                    "--reaction gives {} reaction times for {} players",
+                    n, players
+                ))
+            }
+        };
+        Ok(reactions
+            .into_iter()
+            .map(|reaction| Agent {
+                reaction,
+                ..Agent::default()
+            })
+            .collect())
+    }
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Args {
+            command: Command::default(),
+            seed: None,
+            shuffle: ShuffleVersion::default(),
+            deal: None,
+            deal_index: None,
+            players: 2,
+            deal_method: None,
+            first: First::default(),
+            each_first: false,
+            deck: DeckSpec::default(),
+            events: Events::default(),
+            output: None,
+            rules: Rules::default(),
+            reactions: Vec::new(),
+            games: None,
+            forever: false,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            search: SearchConfig::default(),
+            db: PathBuf::from(store::DEFAULT_PATH),
+            save: true,
+            dedup: None,
+            help: false,
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+
+pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut positional = Vec::new();
+    let (mut pack, mut packs, mut jokers, mut joker_penalty) = (None, None, None, None);
+    let mut deck_file = None;
+    let mut rules_file = None;
+    let (mut leader, mut tens, mut out_of_cards) = (None, None, None);
+    let mut ratscrew = false;
+    let mut events = None;
+    let mut first = None;
+    let mut players_given = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                let seed = parse_value(&arg, &mut args)?;
+                set_seed(&mut parsed, seed)?;
+            }
+            "--seed-hex" => {
+                let value = value_for(&arg, &mut args)?;
+                let digits = value
+                    .strip_prefix("0x")
+                    .or_else(|| value.strip_prefix("0X"))
+                    .unwrap_or(&value);
+                let seed = u64::from_str_radix(digits, 16)
+                    .map_err(|_| format!("invalid value for --seed-hex: '{}'", value))?;
+                set_seed(&mut parsed, seed)?;
+            }
+            "--seed-text" => {
+                let value = value_for(&arg, &mut args)?;
+                set_seed(&mut parsed, seed::hash_seed_text(&value))?;
+            }
+            "--shuffle" => parsed.shuffle = value_for(&arg, &mut args)?.parse()?,
+            "--deal" => set_deal(&mut parsed, value_for(&arg, &mut args)?.parse()?)?,
+            "--deal-file" => {
+                let path = PathBuf::from(value_for(&arg, &mut args)?);
+                let text = fs::read_to_string(&path).map_err(|err| {
+                    format!("could not read deal file {}: {}", path.display(), err)
+                })?;
+                let text = text
+                    .lines()
+                    .map(|line| line.split(COMMENT).next().unwrap_or_default())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let deal = text
+                    .parse()
+                    .map_err(|err| format!("invalid deal file {}: {}", path.display(), err))?;
+                set_deal(&mut parsed, deal)?;
+            }
+            "--deal-index" => parsed.deal_index = Some(value_for(&arg, &mut args)?.parse()?),
+            "--players" => {
+                let players = parse_value(&arg, &mut args)?;
+                if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&players) {
+                    return Err(format!(
+                        "--players must be between {} and {}",
+                        MIN_PLAYERS, MAX_PLAYERS
+                    ));
+                }
+                parsed.players = players;
+                players_given = true;
+            }
+            "--deal-method" => parsed.deal_method = Some(value_for(&arg, &mut args)?.parse()?),
+            "--first" => first = Some(value_for(&arg, &mut args)?.parse::<First>()?),
+            "--each-first" => parsed.each_first = true,
+            "--deck" => pack = Some(value_for(&arg, &mut args)?.parse::<Pack>()?),
+            "--packs" => packs = Some(parse_value(&arg, &mut args)?),
+            "--jokers" => jokers = Some(parse_value(&arg, &mut args)?),
+            "--joker-penalty" => {
+                let penalty = parse_value(&arg, &mut args)?;
+                if penalty > Rank::MAX_JOKER_PENALTY {
+                    return Err(format!(
+                        "--joker-penalty must be at most {}",
+                        Rank::MAX_JOKER_PENALTY
+                    ));
+                }
+                joker_penalty = Some(penalty);
+            }
+            "--deck-file" => deck_file = Some(PathBuf::from(value_for(&arg, &mut args)?)),
+            "--events" => events = Some(value_for(&arg, &mut args)?.parse()?),
+            "--output" => parsed.output = Some(value_for(&arg, &mut args)?.parse()?),
+            "--leader" => leader = Some(value_for(&arg, &mut args)?.parse::<Leader>()?),
+            "--tens" => {
+                let penalty = parse_value(&arg, &mut args)?;
+                if penalty > Rank::MAX_JOKER_PENALTY {
+                    return Err(format!(
+                        "--tens must be at most {}",
+                        Rank::MAX_JOKER_PENALTY
+                    ));
+                }
+                tens = Some(penalty);
+            }
+            "--out-of-cards" => {
+                out_of_cards = Some(value_for(&arg, &mut args)?.parse::<OutOfCards>()?)
+            }
+            "--ratscrew" => ratscrew = true,
+            "--reaction" => {
+                parsed.reactions = value_for(&arg, &mut args)?
+                    .split(',')
+                    .map(str::parse)
+                    .collect::<Result<_, _>>()?
+            }
+            "--rules" => rules_file = Some(PathBuf::from(value_for(&arg, &mut args)?)),
+            "--games" => parsed.games = Some(parse_value(&arg, &mut args)?),
+            "--forever" => parsed.forever = true,
+            "--metric" => parsed.search.metric = value_for(&arg, &mut args)?.parse()?,
+            "--threads" => parsed.threads = parse_value(&arg, &mut args)?,
+            "--restarts" => parsed.search.restarts = Some(parse_value(&arg, &mut args)?),
+            "--iterations" => parsed.search.iterations = parse_value(&arg, &mut args)?,
+            "--temperature" => parsed.search.temperature = parse_value(&arg, &mut args)?,
+            "--top" => parsed.search.top = parse_value(&arg, &mut args)?,
+            "--db" => parsed.db = PathBuf::from(value_for(&arg, &mut args)?),
+            "--no-save" => parsed.save = false,
+            "--dedup" => parsed.dedup = Some(value_for(&arg, &mut args)?.parse()?),
+            "-h" | "--help" => parsed.help = true,
+            _ if arg.starts_with('-') => return Err(format!("unexpected argument '{}'", arg)),
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    parsed.command = match positional.next().as_deref() {
+        None => Command::Run,
+        Some("replay") => {
+            let key = positional
+                .next()
+                .ok_or_else(|| "replay needs a record id or seed".to_string())?;
+            Command::Replay(parse_key(&key)?)
+        }
+        Some("play") => Command::Play,
+        Some("view") => match positional.next() {
+            Some(key) => Command::View(Some(parse_key(&key)?)),
+            None => Command::View(None),
+        },
+        Some("search") => Command::Search,
+        Some("experiment") => Command::Experiment,
+        Some("bench") => Command::Bench,
+        Some(other) => return Err(format!("unknown command '{}'", other)),
+    };
+
+    if let Some(extra) = positional.next() {
+        return Err(format!("unexpected argument '{}'", extra));
+    }
+
+    // A transcript takes stdout for itself.
+    parsed.events = match (events, parsed.output) {
+        (Some(_), Some(_)) => return Err("--events and --output cannot be combined".to_string()),
+        (Some(events), None) => events,
+        (None, Some(_)) => Events::None,
+        (None, None) => Events::default(),
+    };
+    match (&parsed.command, parsed.output) {
+        (Command::Experiment, Some(Format::Text)) => {
+            return Err("experiment summaries are written as json or csv".to_string())
+        }
+        (Command::Experiment, _) | (_, None) => {}
+        (Command::Run | Command::Replay(_), Some(_)) if !parsed.is_batch() => {}
+        (_, Some(_)) => return Err("--output writes a single game, played or replayed".to_string()),
+    }
+    if matches!(parsed.command, Command::Experiment) && (parsed.forever || parsed.deal.is_some()) {
+        return Err("experiment plays a fixed number of shuffled games".to_string());
+    }
+
+    if parsed.forever && parsed.games.is_some() {
+        return Err("--games and --forever cannot be used together".to_string());
+    }
+
+    if parsed.deal.is_some() && (parsed.seed.is_some() || parsed.is_batch() || parsed.players != 2)
+    {
+        return Err(
+            "--deal cannot be combined with a seed, --players, --games or --forever".to_string(),
+        );
+    }
+
+    if let Some(path) = rules_file {
+        parsed.rules = Rules::from_file(&path)
+            .map_err(|err| format!("could not read rules file {}: {}", path.display(), err))?;
+    }
+    if let Some(leader) = leader {
+        parsed.rules.leader = leader;
+    }
+    if let Some(tens) = tens {
+        parsed.rules.penalties.set(Rank::Ten, tens);
+    }
+    if let Some(out_of_cards) = out_of_cards {
+        parsed.rules.out_of_cards = out_of_cards;
+    }
+    if ratscrew && !parsed.rules.slaps.enabled() {
+        parsed.rules.slaps = SlapRules::ratscrew();
+    }
+    if !parsed.reactions.is_empty() && !parsed.rules.slaps.enabled() {
+        return Err("--reaction needs --ratscrew or slap rules".to_string());
+    }
+    if matches!(parsed.command, Command::Play | Command::View(_)) && parsed.is_batch() {
+        return Err("play and view take one game at a time, not --games or --forever".to_string());
+    }
+    if matches!(parsed.command, Command::Search) {
+        if parsed.rules.slaps.enabled() {
+            return Err("search plays rank-only deals, which cannot be slapped".to_string());
+        }
+        if players_given || parsed.deal_method.is_some() || first.is_some() {
+            return Err(
+                "search deals two hands that player one leads, so --players, --deal-method and --first do not apply"
+                    .to_string(),
+            );
+        }
+    }
+    if matches!(parsed.command, Command::Bench) {
+        if parsed.rules.slaps.enabled() {
+            return Err("bench plays rank-only deals, which cannot be slapped".to_string());
+        }
+        if parsed.forever || parsed.deal.is_some() {
+            return Err("bench plays a fixed number of shuffled deals".to_string());
+        }
+    }
+
+    let deck_options = pack.is_some() || packs.is_some() || jokers.is_some();
+    if deck_file.is_some() && (deck_options || joker_penalty.is_some()) {
+        return Err(
+            "--deck-file cannot be combined with --deck, --packs, --jokers or --joker-penalty"
+                .to_string(),
+        );
+    }
+    if packs == Some(0) {
+        return Err("--packs must be at least 1".to_string());
+    }
+
+    parsed.deck = match deck_file {
+        Some(path) => DeckSpec::from_file(&path)
+            .map_err(|err| format!("could not read deck file {}: {}", path.display(), err))?,
+        None => DeckSpec::Packs {
+            pack: pack.unwrap_or_default(),
+            packs: packs.unwrap_or(1),
+            jokers: jokers.unwrap_or(0),
+            joker_penalty: joker_penalty.unwrap_or(0),
+        },
+    };
+    if let Some(StartingDeal::Ranks(deal)) = &parsed.deal {
+        deal.to_cards(&parsed.deck.cards(), &parsed.rules)
+            .map_err(|err| format!("the deal does not fit the deck ({}): {}", parsed.deck, err))?;
+    }
+    if let Some(StartingDeal::Cards(hands)) = &parsed.deal {
+        hands.check(&parsed.deck).map_err(|err| {
+            format!(
+                "the deal does not match the deck ({}): {}",
+                parsed.deck, err
+            )
+        })?;
+    }
+    if parsed.deal.is_some() && parsed.deal_method.is_some() {
+        return Err("--deal is already dealt and cannot take a --deal-method".to_string());
+    }
+    parsed
+        .dealing()
+        .check(parsed.deck.len(), parsed.players)
+        .map_err(|err| format!("invalid --deal-method: {}", err))?;
+
+    if parsed.dedup.is_some() && (!parsed.save || !matches!(parsed.command, Command::Run)) {
+        return Err("--dedup applies to games saved to the results file".to_string());
+    }
+    if parsed.each_first {
+        if first.is_some() {
+            return Err("--each-first already picks every leader in turn".to_string());
+        }
+        if !matches!(parsed.command, Command::Run) || !parsed.is_batch() {
+            return Err("--each-first needs --games or --forever".to_string());
+        }
+    }
+    if let Some(first) = first {
+        if matches!(parsed.command, Command::Replay(_)) {
+            return Err("--first cannot change a replay".to_string());
+        }
+        let players = match &parsed.deal {
+            Some(StartingDeal::Ranks(deal)) => deal.hands().len(),
+            Some(StartingDeal::Cards(hands)) => hands.hands().len(),
+            None => parsed.players,
+        };
+        if let First::Player(player) = first {
+            if player.index() >= players {
+                return Err(format!(
+                    "--first {} is not at a table of {}",
+                    player.number(),
+                    players
+                ));
+            }
+        }
+        parsed.first = first;
+    }
+    if parsed.deck.len() < parsed.players {
+        return Err(format!(
+            "a deck of {} cards cannot be dealt to {} players",
+            parsed.deck.len(),
+            parsed.players
+        ));
+    }
+
+    if let Some(index) = &parsed.deal_index {
+        if parsed.deal.is_some() || parsed.seed.is_some() {
+            return Err(
+                "--deal-index cannot be combined with a seed, --deal or --deal-file".to_string(),
+            );
+        }
+        if !matches!(
+            parsed.command,
+            Command::Run | Command::Play | Command::View(None)
+        ) {
+            return Err("--deal-index deals games to run, play or view".to_string());
+        }
+
+        let space = parsed.deal_space();
+        let deal = space.unrank(index)?;
+        if !parsed.is_batch() {
+            let hands = deal
+                .to_cards(&parsed.deck.cards(), &parsed.rules)
+                .expect("an indexed deal holds the deck");
+            parsed.deal = Some(StartingDeal::Cards(Hands::new(
+                hands.into_iter().map(Vec::from).collect::<Vec<_>>(),
+            )));
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn value_for(flag: &str, args: &mut impl Iterator<Item = String>) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("missing value for {}", flag))
+}
+
+fn parse_value<T: FromStr>(
+    flag: &str,
+    args: &mut impl Iterator<Item = String>,
+) -> Result<T, String> {
+    let value = value_for(flag, args)?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {}: '{}'", flag, value))
+}
+
+fn parse_key(key: &str) -> Result<u64, String> {
+    key.parse()
+        .map_err(|_| format!("invalid record id or seed: '{}'", key))
+}
+
+fn set_deal(parsed: &mut Args, deal: StartingDeal) -> Result<(), String> {
+    if parsed.deal.replace(deal).is_some() {
+        return Err("only one of --deal or --deal-file may be given".to_string());
+    }
+    Ok(())
+}
+
+fn set_seed(parsed: &mut Args, seed: u64) -> Result<(), String> {
+    if parsed.seed.replace(seed).is_some() {
+        return Err("only one of --seed, --seed-hex or --seed-text may be given".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_line(line: &str) -> Result<Args, String> {
+        parse(line.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn builds_the_deck_from_flags() {
+        let args = parse_line("--deck euchre --packs 2 --jokers 1 --joker-penalty 4").unwrap();
+        assert_eq!(args.deck.len(), 49);
+        assert_eq!(args.deck.cards()[48].rank, Rank::Joker(4));
+
+        assert!(parse_line("--packs 0").is_err());
+        assert!(parse_line("--joker-penalty 10").is_err());
+        assert!(parse_line("--deck skat").is_err());
+        assert!(parse_line("--deck-file deck.txt --jokers 2").is_err());
+        assert!(parse_line("--deck-file /nonexistent/deck.txt").is_err());
+    }
+
+    #[test]
+    fn sets_the_rules_from_flags() {
+        let args = parse_line("--tens 5 --leader legacy --out-of-cards forfeit").unwrap();
+        assert_eq!(args.rules.penalty(Rank::Ten), Some(5));
+        assert_eq!(args.rules.penalty(Rank::Jack), Some(1));
+        assert_eq!(args.rules.leader, Leader::Legacy);
+        assert_eq!(args.rules.out_of_cards, OutOfCards::Forfeit);
+        assert_eq!(
+            parse_line("--tens 0").unwrap().rules.penalty(Rank::Ten),
+            None
+        );
+
+        assert!(parse_line("--tens 10").is_err());
+        assert!(parse_line("--leader dealer").is_err());
+        assert!(parse_line("--rules /nonexistent/rules.toml").is_err());
+    }
+
+    #[test]
+    fn checks_the_deal_method_against_the_table() {
+        let args = parse_line("--players 3 --deal-method packets:5").unwrap();
+        assert_eq!(args.dealing(), DealMethod::Packets(5));
+        assert_eq!(
+            parse_line("--players 3").unwrap().dealing(),
+            DealMethod::Alternate
+        );
+
+        assert!(parse_line("--deal-method packets:52").is_err());
+        assert!(parse_line("--players 4 --deal-method packets:18").is_err());
+        assert!(parse_line("--deal-method handicap:26").is_err());
+        assert!(parse_line("--deal -A/K- --deal-method split").is_err());
+    }
+
+    #[test]
+    fn keeps_search_to_two_hands_led_by_player_one() {
+        assert!(parse_line("search --restarts 1").is_ok());
+        for flags in [
+            "--players 2",
+            "--players 3",
+            "--deal-method alternate",
+            "--first 2",
+        ] {
+            assert!(
+                parse_line(&format!("search {}", flags)).is_err(),
+                "{}",
+                flags
+            );
+        }
+    }
+
+    #[test]
+    fn reads_seeds_in_every_form() {
+        assert_eq!(parse_line("--seed 42").unwrap().seed, Some(42));
+        assert_eq!(parse_line("--seed-hex 0x2A").unwrap().seed, Some(42));
+        assert_eq!(parse_line("--seed-hex ff").unwrap().seed, Some(255));
+        assert_eq!(
+            parse_line("--seed-text foobar").unwrap().seed,
+            Some(seed::hash_seed_text("foobar"))
+        );
+        assert!(matches!(
+            parse_line("replay 7").unwrap().command,
+            Command::Replay(7)
+        ));
+
+        for line in [
+            "--seed -1",
+            "--seed-hex 0xg",
+            "--seed 1 --seed-hex 1",
+            "--seed",
+            "replay x",
+        ] {
+            assert!(parse_line(line).is_err(), "{}", line);
+        }
+    }
+}
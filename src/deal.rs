@@ -0,0 +1,588 @@
+// CRATES
+
+use crate::player::{MAX_PLAYERS, MIN_PLAYERS};
+use crate::rules::Rules;
+use crate::seed::{fnv1a, FNV_OFFSET};
+use crate::{Card, DeckSpec, Player, Rank};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+// CONSTANTS
+
+const BLANK: char = '-';
+const HAND_SEPARATOR: char = '/';
+/// Separates the leader from the deal in a written canonical deal.
+const FIRST_SEPARATOR: char = ':';
+
+// STRUCTS + METHODS
+
+/// A starting position reduced to what matters for play: the penalty value
+/// of each card in every hand, front of the hand first.
+///
+/// Written the way the search literature writes deals, one character per
+/// card (`-` for a non-penalty card, otherwise `J`, `Q`, `K` or `A` for
+/// penalties of one to four, or a digit for larger penalties) and the hands
+/// separated by `/`, e.g. `---K---Q-KQAJ-----AAJ--J--/----------Q----KQ-J-----KA`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Deal {
+    hands: Vec<Vec<u8>>,
+}
+
+impl Deal {
+    pub fn new(hands: impl Into<Vec<Vec<u8>>>) -> Self {
+        Deal {
+            hands: hands.into(),
+        }
+    }
+
+    /// Reduce dealt hands to the penalty each card demands under `rules`.
+    pub fn from_cards(decks: &[VecDeque<Card>], rules: &Rules) -> Self {
+        Deal::new(
+            decks
+                .iter()
+                .map(|deck| {
+                    deck.iter()
+                        .map(|card| rules.penalty(card.rank).unwrap_or(0))
+                        .collect()
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    pub fn hands(&self) -> &[Vec<u8>] {
+        &self.hands
+    }
+
+    /// Deal the cards of `deck` out the way this deal says: every card takes
+    /// the next card of the deck with its penalty under `rules`, so a
+    /// standard deal of the standard deck uses each card exactly once. Fails
+    /// if the deck runs out of cards with some penalty.
+    pub fn to_cards(&self, deck: &[Card], rules: &Rules) -> Result<Vec<VecDeque<Card>>, String> {
+        let mut by_penalty = HashMap::<u8, VecDeque<Card>>::new();
+        for &card in deck {
+            by_penalty
+                .entry(rules.penalty(card.rank).unwrap_or(0))
+                .or_default()
+                .push_back(card);
+        }
+
+        self.hands
+            .iter()
+            .map(|hand| {
+                hand.iter()
+                    .map(|&penalty| {
+                        by_penalty
+                            .get_mut(&penalty)
+                            .and_then(VecDeque::pop_front)
+                            .ok_or_else(|| match penalty {
+                                0 => "the deck has too few number cards for the deal".to_string(),
+                                _ => format!(
+                                    "the deck has too few cards demanding {} for the deal",
+                                    penalty
+                                ),
+                            })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Deal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, hand) in self.hands.iter().enumerate() {
+            if i > 0 {
+                write!(f, "{}", HAND_SEPARATOR)?;
+            }
+            for &penalty in hand {
+                match penalty_rank(penalty) {
+                    Some(rank) => write!(f, "{}", rank)?,
+                    None if penalty > 0 => write!(f, "{}", penalty)?,
+                    None => write!(f, "{}", BLANK)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Deal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hands = s.trim().split(HAND_SEPARATOR).collect::<Vec<_>>();
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&hands.len()) {
+            return Err(format!(
+                "expected {} to {} hands separated by '{}', found {}",
+                MIN_PLAYERS,
+                MAX_PLAYERS,
+                HAND_SEPARATOR,
+                hands.len()
+            ));
+        }
+
+        let parse_hand = |hand: &str| {
+            hand.trim()
+                .chars()
+                .map(|c| match c {
+                    BLANK => Ok(0),
+                    '1'..='9' if penalty_rank(c as u8 - b'0').is_none() => Ok(c as u8 - b'0'),
+                    _ => Rank::ALL
+                        .into_iter()
+                        .find(|rank| rank.to_string() == c.to_string())
+                        .and_then(|rank| rank.penalty_value())
+                        .ok_or_else(|| {
+                            format!(
+                                "unexpected '{}' in deal, expected -, J, Q, K, A or a digit from 5 to 9",
+                                c
+                            )
+                        }),
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let hands = hands
+            .into_iter()
+            .map(parse_hand)
+            .collect::<Result<Vec<_>, _>>()?;
+        check_dealt(hands.iter().map(Vec::len))?;
+        Ok(Deal::new(hands))
+    }
+}
+
+/// A deal as the game sees it: the penalty value of every card in every hand
+/// and who leads the first trick. Shuffles that differ only in suits, or in
+/// which number cards fell where, play out the same way and have the same
+/// canonical deal.
+///
+/// Written as the leader's number, `:` and the rank-only deal, e.g.
+/// `2:---K---Q-KQAJ-----AAJ--J--/----------Q----KQ-J-----KA`; without the
+/// number player one leads.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CanonicalDeal {
+    deal: Deal,
+    first: Player,
+}
+
+impl CanonicalDeal {
+    pub fn new(deal: Deal, first: Player) -> Self {
+        CanonicalDeal { deal, first }
+    }
+
+    pub fn from_cards(decks: &[VecDeque<Card>], rules: &Rules, first: Player) -> Self {
+        CanonicalDeal::new(Deal::from_cards(decks, rules), first)
+    }
+
+    pub fn deal(&self) -> &Deal {
+        &self.deal
+    }
+
+    pub fn first(&self) -> Player {
+        self.first
+    }
+
+    /// A 64-bit FNV-1a hash of the leader's seat and then each hand's
+    /// penalty values after a `255` marker. Unlike `Hash` it is the same on
+    /// every platform and in every version, so results can be keyed by it
+    /// outside the program; equal deals always hash alike, but check for
+    /// equality before trusting a match.
+    pub fn stable_hash(&self) -> u64 {
+        stable_hash(self.first, self.deal.hands().iter().map(Vec::as_slice))
+    }
+
+    /// Whether this is the deal with these hands of penalty values, led by
+    /// `first`.
+    pub fn holds(&self, hands: &[&[u8]], first: Player) -> bool {
+        self.first == first
+            && self
+                .deal
+                .hands()
+                .iter()
+                .map(Vec::as_slice)
+                .eq(hands.iter().copied())
+    }
+
+    /// Turn the table so the leader sits in seat one. Play passes clockwise
+    /// whoever sits where, so this is the same game with the players
+    /// renumbered; with two players it swaps the hands when player two
+    /// leads.
+    pub fn seated(&self) -> Self {
+        let mut hands = self.deal.hands().to_vec();
+        hands.rotate_left(self.first.index());
+        CanonicalDeal::new(Deal::new(hands), Player::ONE)
+    }
+}
+
+impl fmt::Display for CanonicalDeal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}{}", self.first.number(), FIRST_SEPARATOR, self.deal)
+    }
+}
+
+impl FromStr for CanonicalDeal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((first, deal)) = s.split_once(FIRST_SEPARATOR) else {
+            return Ok(CanonicalDeal::new(s.parse()?, Player::ONE));
+        };
+
+        let deal = deal.parse::<Deal>()?;
+        match first.trim().parse::<usize>() {
+            Ok(number) if (1..=deal.hands().len()).contains(&number) => {
+                Ok(CanonicalDeal::new(deal, Player::new(number - 1)))
+            }
+            _ => Err(format!(
+                "invalid first player '{}' for a deal of {} hands",
+                first,
+                deal.hands().len()
+            )),
+        }
+    }
+}
+
+/// Which deals count as the same game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Equivalence {
+    /// The same penalty values in every hand, with the same player leading.
+    Deal,
+    /// The same once the table is turned so each deal's leader is player
+    /// one.
+    Seats,
+}
+
+impl Equivalence {
+    pub fn canonical(&self, deal: CanonicalDeal) -> CanonicalDeal {
+        match self {
+            Equivalence::Deal => deal,
+            Equivalence::Seats => deal.seated(),
+        }
+    }
+}
+
+impl fmt::Display for Equivalence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Equivalence::Deal => write!(f, "deal"),
+            Equivalence::Seats => write!(f, "seats"),
+        }
+    }
+}
+
+impl FromStr for Equivalence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "deal" => Ok(Equivalence::Deal),
+            "seats" => Ok(Equivalence::Seats),
+            _ => Err(format!(
+                "unknown equivalence '{}', expected deal or seats",
+                s
+            )),
+        }
+    }
+}
+
+/// A starting position given card for card, each hand front first.
+///
+/// Written with the cards separated by spaces or commas and the hands by
+/// `/`, e.g. `10♤ QH AS ... / 2D 7C ...`; it displays the way `Card` does, and
+/// parses back to the same hands.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hands {
+    hands: Vec<Vec<Card>>,
+}
+
+impl Hands {
+    pub fn new(hands: impl Into<Vec<Vec<Card>>>) -> Self {
+        Hands {
+            hands: hands.into(),
+        }
+    }
+
+    pub fn hands(&self) -> &[Vec<Card>] {
+        &self.hands
+    }
+
+    /// Every card dealt, the first hand first.
+    pub fn cards(&self) -> Vec<Card> {
+        self.hands.iter().flatten().copied().collect()
+    }
+
+    pub fn to_decks(&self) -> Vec<VecDeque<Card>> {
+        self.hands
+            .iter()
+            .map(|hand| hand.iter().copied().collect())
+            .collect()
+    }
+
+    /// Check the hands hold exactly the cards of `deck`: the same number of
+    /// cards, none missing and none dealt twice.
+    pub fn check(&self, deck: &DeckSpec) -> Result<(), String> {
+        let dealt = self.hands.iter().map(Vec::len).sum::<usize>();
+        if dealt != deck.len() {
+            return Err(format!(
+                "the deal has {} cards but the deck has {}",
+                dealt,
+                deck.len()
+            ));
+        }
+
+        let mut left = HashMap::new();
+        for card in deck.cards() {
+            *left.entry(card).or_insert(0usize) += 1;
+        }
+        for card in self.hands.iter().flatten() {
+            match left.get_mut(card) {
+                Some(0) => {
+                    return Err(format!(
+                        "duplicate {}: dealt more times than the deck holds it",
+                        card
+                    ))
+                }
+                Some(count) => *count -= 1,
+                None => return Err(format!("{} is not in the deck", card)),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Hands {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, hand) in self.hands.iter().enumerate() {
+            if i > 0 {
+                write!(f, " {} ", HAND_SEPARATOR)?;
+            }
+            for (j, card) in hand.iter().enumerate() {
+                if j > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{}", card)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Hands {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hands = s.split(HAND_SEPARATOR).collect::<Vec<_>>();
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&hands.len()) {
+            return Err(format!(
+                "expected {} to {} hands separated by '{}', found {}",
+                MIN_PLAYERS,
+                MAX_PLAYERS,
+                HAND_SEPARATOR,
+                hands.len()
+            ));
+        }
+
+        let parse_hand = |hand: &str| {
+            hand.split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|token| !token.is_empty())
+                .map(str::parse)
+                .collect::<Result<Vec<Card>, _>>()
+        };
+
+        let hands = hands
+            .into_iter()
+            .map(parse_hand)
+            .collect::<Result<Vec<_>, _>>()?;
+        check_dealt(hands.iter().map(Vec::len))?;
+        Ok(Hands::new(hands))
+    }
+}
+
+// HELPER FUNCTIONS
+
+/// [`CanonicalDeal::stable_hash`] of hands of penalty values not yet made
+/// into a deal.
+pub(crate) fn stable_hash<'a>(first: Player, hands: impl IntoIterator<Item = &'a [u8]>) -> u64 {
+    let mut hash = fnv1a(FNV_OFFSET, &[first.index() as u8]);
+    for hand in hands {
+        hash = fnv1a(hash, &[u8::MAX]);
+        hash = fnv1a(hash, hand);
+    }
+    hash
+}
+
+/// Check a deal gives cards to at least two players, so there is a game to
+/// play.
+fn check_dealt(hand_sizes: impl Iterator<Item = usize>) -> Result<(), String> {
+    if hand_sizes.filter(|&size| size > 0).count() < 2 {
+        return Err("a deal must give cards to at least two players".to_string());
+    }
+    Ok(())
+}
+
+/// The rank written for `penalty` in deal notation, which always uses the
+/// standard letters.
+fn penalty_rank(penalty: u8) -> Option<Rank> {
+    if penalty == 0 {
+        return None;
+    }
+    Rank::ALL
+        .into_iter()
+        .find(|rank| rank.penalty_value() == Some(penalty))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// FNV-1a of the bytes 1, 255, 0, 1, 255, 2, 0.
+    const PINNED_HASH: u64 = 7425438244112151961;
+
+    #[test]
+    fn rejects_deals_without_two_hands_of_cards() {
+        for deal in ["/", "-/", "A/", "//--"] {
+            assert!(deal.parse::<Deal>().is_err(), "{}", deal);
+        }
+        for hands in ["/", "AS/", "/ / 2H"] {
+            assert!(hands.parse::<Hands>().is_err(), "{}", hands);
+        }
+        assert!("-/-".parse::<Deal>().is_ok());
+    }
+
+    #[test]
+    fn reads_back_the_deals_it_prints() {
+        let mann = "---K---Q-KQAJ-----AAJ--J--/----------Q----KQ-J-----KA";
+        let deal = mann.parse::<Deal>().unwrap();
+        assert_eq!(deal.to_string(), mann);
+        assert_eq!(deal.hands()[0][..4], [0, 0, 0, 3]);
+        assert_eq!(deal.hands()[1].len(), 26);
+
+        let jokers = "-7J/Q9-/--5".parse::<Deal>().unwrap();
+        assert_eq!(
+            jokers.hands(),
+            [vec![0, 7, 1], vec![2, 9, 0], vec![0, 0, 5]]
+        );
+        assert_eq!(jokers.to_string(), "-7J/Q9-/--5");
+        assert_eq!(" -A / K- ".parse::<Deal>(), "-A/K-".parse::<Deal>());
+        for invalid in ["-X/--", "-1/--", "-4/--", "--"] {
+            assert!(invalid.parse::<Deal>().is_err(), "{}", invalid);
+        }
+
+        let canonical = format!("2:{}", mann).parse::<CanonicalDeal>().unwrap();
+        assert_eq!(canonical.first(), Player::TWO);
+        assert_eq!(canonical.deal(), &deal);
+        assert_eq!(canonical.to_string(), format!("2:{}", mann));
+        assert_eq!(mann.parse::<CanonicalDeal>().unwrap().first(), Player::ONE);
+        assert!(format!("3:{}", mann).parse::<CanonicalDeal>().is_err());
+        assert_eq!(
+            canonical.seated(),
+            CanonicalDeal::new(
+                Deal::new(vec![deal.hands()[1].clone(), deal.hands()[0].clone()]),
+                Player::ONE
+            )
+        );
+    }
+
+    #[test]
+    fn reads_back_the_hands_it_prints() {
+        let hands = Hands::new(
+            crate::DealMethod::classic(3)
+                .deal(&crate::seed::shuffled_deck(7), 3)
+                .into_iter()
+                .map(Vec::from)
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(hands.to_string().parse::<Hands>(), Ok(hands.clone()));
+        assert_eq!(hands.check(&DeckSpec::default()), Ok(()));
+        assert_eq!(
+            "AS, 10♤ ,QH/ td 2c".parse::<Hands>(),
+            "A♤ 10♤ Q♡ / 10♢ 2♧".parse::<Hands>()
+        );
+        assert!("AS 1S / 2C".parse::<Hands>().is_err());
+    }
+
+    #[test]
+    fn checks_hands_against_the_deck() {
+        let mut cards = crate::create_deck();
+        let deal = |cards: &[Card]| {
+            Hands::new(vec![cards[..26].to_vec(), cards[26..].to_vec()]).check(&DeckSpec::default())
+        };
+        assert_eq!(deal(&cards), Ok(()));
+        assert!(deal(&cards[1..]).is_err());
+
+        cards[1] = cards[0];
+        assert!(deal(&cards).unwrap_err().starts_with("duplicate"));
+
+        let spec = DeckSpec::Packs {
+            pack: crate::Pack::Piquet,
+            packs: 1,
+            jokers: 0,
+            joker_penalty: 0,
+        };
+        let cards = crate::create_deck();
+        let hands = Hands::new(vec![cards[..16].to_vec(), cards[16..32].to_vec()]);
+        assert!(hands
+            .check(&spec)
+            .unwrap_err()
+            .ends_with("is not in the deck"));
+    }
+
+    #[test]
+    fn hashes_canonical_deals_stably() {
+        let deal = "2:-J/Q-".parse::<CanonicalDeal>().unwrap();
+        assert_eq!(deal.stable_hash(), PINNED_HASH);
+        assert_eq!(
+            deal.stable_hash(),
+            stable_hash(Player::TWO, [&[0, 1][..], &[2, 0][..]])
+        );
+        assert!(deal.holds(&[&[0, 1], &[2, 0]], Player::TWO));
+        assert!(!deal.holds(&[&[0, 1], &[2, 0]], Player::ONE));
+        assert_ne!(deal.stable_hash(), deal.seated().stable_hash());
+    }
+
+    #[test]
+    fn draws_cards_from_the_deck() {
+        let rules = Rules::default();
+        let deal = "---K---Q-KQAJ-----AAJ--J--/----------Q----KQ-J-----KA"
+            .parse::<Deal>()
+            .unwrap();
+        let hands = deal.to_cards(&crate::create_deck(), &rules).unwrap();
+        assert_eq!(Deal::from_cards(&hands, &rules), deal);
+
+        let mut cards = hands.iter().flatten().copied().collect::<Vec<_>>();
+        cards.sort_by_key(|card| card.to_string());
+        cards.dedup();
+        assert_eq!(cards.len(), 52);
+    }
+
+    #[test]
+    fn keeps_ranks_that_share_a_penalty() {
+        let mut rules = Rules::default();
+        rules.penalties.set(Rank::Ten, 2);
+        let hands = "QQQQQQQQ/--"
+            .parse::<Deal>()
+            .unwrap()
+            .to_cards(&crate::create_deck(), &rules)
+            .unwrap();
+        let tens = hands[0]
+            .iter()
+            .filter(|card| card.rank == Rank::Ten)
+            .count();
+        assert_eq!(tens, 4);
+        assert!("QQQQQQQQQ/--"
+            .parse::<Deal>()
+            .unwrap()
+            .to_cards(&crate::create_deck(), &rules)
+            .is_err());
+    }
+
+    #[test]
+    fn refuses_deals_the_deck_cannot_hold() {
+        let mut rules = Rules::default();
+        for rank in Rank::ALL {
+            rules.penalties.set(rank, rules.penalty(rank).unwrap_or(1));
+        }
+        let deal = "-A/-K".parse::<Deal>().unwrap();
+        assert!(deal.to_cards(&crate::create_deck(), &rules).is_err());
+    }
+}
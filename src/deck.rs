@@ -0,0 +1,456 @@
+// CRATES
+
+use crate::{deal_round_robin, split_deck, Card, Rank, Suit};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+// CONSTANTS
+
+const COMMENT: char = '#';
+
+// STRUCTS + METHODS
+
+/// The packs a deck can be built from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Pack {
+    /// The full 52-card French pack.
+    #[default]
+    Standard,
+    /// Sevens to aces in each suit.
+    Piquet,
+    /// Nines to aces in each suit.
+    Euchre,
+}
+
+impl Pack {
+    pub fn ranks(&self) -> &'static [Rank] {
+        match self {
+            Pack::Standard => &Rank::ALL,
+            Pack::Piquet => &Rank::ALL[5..],
+            Pack::Euchre => &Rank::ALL[7..],
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.ranks().len() * Suit::ALL.len()
+    }
+}
+
+impl fmt::Display for Pack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Pack::Standard => "standard",
+                Pack::Piquet => "piquet",
+                Pack::Euchre => "euchre",
+            }
+        )
+    }
+}
+
+impl FromStr for Pack {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Pack::Standard),
+            "piquet" => Ok(Pack::Piquet),
+            "euchre" => Ok(Pack::Euchre),
+            _ => Err(format!(
+                "unknown pack '{}', expected standard, piquet or euchre",
+                s
+            )),
+        }
+    }
+}
+
+/// The cards a game is dealt from, before shuffling.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeckSpec {
+    /// `packs` copies of `pack` shuffled together, plus `jokers` jokers that
+    /// each demand `joker_penalty` cards.
+    Packs {
+        pack: Pack,
+        packs: usize,
+        jokers: usize,
+        joker_penalty: u8,
+    },
+    /// An explicit list of cards, usually read from a deck file.
+    Cards(Vec<Card>),
+}
+
+impl Default for DeckSpec {
+    fn default() -> Self {
+        DeckSpec::Packs {
+            pack: Pack::Standard,
+            packs: 1,
+            jokers: 0,
+            joker_penalty: 0,
+        }
+    }
+}
+
+impl DeckSpec {
+    /// Read a deck file: cards in the notation `Card` displays and parses,
+    /// separated by whitespace or commas, with `#` starting a comment.
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        text.parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Every card in the deck, in a fixed order. The default spec gives
+    /// exactly `create_deck()`, so seeds deal the same games as before.
+    pub fn cards(&self) -> Vec<Card> {
+        match self {
+            DeckSpec::Packs {
+                pack,
+                packs,
+                jokers,
+                joker_penalty,
+            } => {
+                let mut deck = Vec::with_capacity(pack.size() * packs + jokers);
+                for _ in 0..*packs {
+                    for &suit in &Suit::ALL {
+                        for &rank in pack.ranks() {
+                            deck.push(Card { rank, suit });
+                        }
+                    }
+                }
+                deck.extend((0..*jokers).map(|n| Card {
+                    rank: Rank::Joker(*joker_penalty),
+                    suit: Suit::ALL[n % 2],
+                }));
+                deck
+            }
+            DeckSpec::Cards(cards) => cards.clone(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            DeckSpec::Packs {
+                pack,
+                packs,
+                jokers,
+                ..
+            } => pack.size() * packs + jokers,
+            DeckSpec::Cards(cards) => cards.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Display for DeckSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeckSpec::Packs {
+                pack,
+                packs,
+                jokers,
+                joker_penalty,
+            } => {
+                write!(f, "{} × {} ({} cards)", packs, pack, pack.size())?;
+                if *jokers > 0 {
+                    write!(f, " + {} jokers", jokers)?;
+                    if *joker_penalty > 0 {
+                        write!(f, " demanding {}", joker_penalty)?;
+                    }
+                }
+                Ok(())
+            }
+            DeckSpec::Cards(cards) => write!(f, "{} listed cards", cards.len()),
+        }
+    }
+}
+
+impl FromStr for DeckSpec {
+    type Err = String;
+
+    /// Parse the contents of a deck file.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cards = s
+            .lines()
+            .map(|line| line.split(COMMENT).next().unwrap_or_default())
+            .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+            .filter(|token| !token.is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<Card>, _>>()?;
+
+        if cards.is_empty() {
+            return Err("deck file lists no cards".to_string());
+        }
+        Ok(DeckSpec::Cards(cards))
+    }
+}
+
+/// How a shuffled deck is dealt out to the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DealMethod {
+    /// One card at a time to each player in turn, as at the table.
+    Alternate,
+    /// Cut the deck into one run of cards per player, the first player
+    /// taking the top run.
+    Split,
+    /// Packets of this many cards to each player in turn.
+    Packets(usize),
+    /// Alternately, but with the first player dealt this many cards fewer
+    /// than an even share; the others take them in turn.
+    Handicap(usize),
+}
+
+impl DealMethod {
+    /// How games have always been dealt when no method is given: two
+    /// players split the deck in half and bigger tables alternate, so saved
+    /// seeds keep their deals.
+    pub fn classic(players: usize) -> Self {
+        if players == 2 {
+            return DealMethod::Split;
+        }
+        DealMethod::Alternate
+    }
+
+    /// Check the method can deal `cards` cards to `players` players.
+    pub fn check(&self, cards: usize, players: usize) -> Result<(), String> {
+        match *self {
+            DealMethod::Packets(0) => Err("packets must hold at least one card".to_string()),
+            DealMethod::Packets(size) if cards.div_ceil(size) < players => Err(format!(
+                "packets of {} leave player {} no cards from a deck of {}",
+                size,
+                cards.div_ceil(size) + 1,
+                cards
+            )),
+            DealMethod::Handicap(handicap) if handicap >= cards.div_ceil(players) => Err(format!(
+                "a handicap of {} leaves the first player no cards from a deck of {}",
+                handicap, cards
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn deal(&self, deck: &[Card], players: usize) -> Vec<VecDeque<Card>> {
+        match *self {
+            DealMethod::Alternate => deal_round_robin(deck, players),
+            DealMethod::Split if players == 2 => split_deck(deck).into(),
+            DealMethod::Split => {
+                let (share, extra) = (deck.len() / players, deck.len() % players);
+                let mut rest = deck;
+                (0..players)
+                    .map(|i| {
+                        let (hand, after) = rest.split_at(share + usize::from(i < extra));
+                        rest = after;
+                        hand.iter().copied().collect()
+                    })
+                    .collect()
+            }
+            DealMethod::Packets(size) => {
+                let mut hands = vec![VecDeque::new(); players];
+                for (i, packet) in deck.chunks(size.max(1)).enumerate() {
+                    hands[i % players].extend(packet);
+                }
+                hands
+            }
+            DealMethod::Handicap(handicap) => {
+                let mut sizes = deal_round_robin(deck, players)
+                    .iter()
+                    .map(VecDeque::len)
+                    .collect::<Vec<_>>();
+                let handicap = handicap.min(sizes[0]);
+                sizes[0] -= handicap;
+                for i in 0..handicap {
+                    sizes[1 + i % (players - 1)] += 1;
+                }
+
+                let mut hands = vec![VecDeque::new(); players];
+                let mut seats = (0..players).cycle();
+                for &card in deck {
+                    let seat = seats
+                        .by_ref()
+                        .find(|&seat| hands[seat].len() < sizes[seat])
+                        .unwrap();
+                    hands[seat].push_back(card);
+                }
+                hands
+            }
+        }
+    }
+}
+
+impl fmt::Display for DealMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DealMethod::Alternate => write!(f, "alternate"),
+            DealMethod::Split => write!(f, "split"),
+            DealMethod::Packets(size) => write!(f, "packets:{}", size),
+            DealMethod::Handicap(handicap) => write!(f, "handicap:{}", handicap),
+        }
+    }
+}
+
+impl FromStr for DealMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "unknown deal method '{}', expected alternate, split, packets:<n> or handicap:<n>",
+                s
+            )
+        };
+        let (method, size) = match s.split_once(':') {
+            Some((method, size)) => (method, Some(size.parse().map_err(|_| invalid())?)),
+            None => (s, None),
+        };
+
+        match (method, size) {
+            ("alternate", None) => Ok(DealMethod::Alternate),
+            ("split", None) => Ok(DealMethod::Split),
+            ("packets", Some(size)) => Ok(DealMethod::Packets(size)),
+            ("handicap", Some(handicap)) => Ok(DealMethod::Handicap(handicap)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Serialize for DealMethod {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DealMethod {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_decks_from_packs() {
+        assert_eq!(DeckSpec::default().cards(), crate::create_deck());
+
+        for (pack, size, lowest) in [
+            (Pack::Standard, 52, Rank::Two),
+            (Pack::Piquet, 32, Rank::Seven),
+            (Pack::Euchre, 24, Rank::Nine),
+        ] {
+            assert_eq!(pack.to_string().parse::<Pack>(), Ok(pack));
+            assert_eq!(pack.size(), size);
+            assert_eq!(pack.ranks()[0], lowest);
+        }
+        assert!("skat".parse::<Pack>().is_err());
+
+        let spec = DeckSpec::Packs {
+            pack: Pack::Piquet,
+            packs: 2,
+            jokers: 3,
+            joker_penalty: 5,
+        };
+        let cards = spec.cards();
+        assert_eq!(cards.len(), 67);
+        assert_eq!(spec.len(), 67);
+        let jokers = cards
+            .iter()
+            .filter(|card| card.rank == Rank::Joker(5))
+            .map(|card| card.suit)
+            .collect::<Vec<_>>();
+        assert_eq!(jokers, [Suit::Spade, Suit::Heart, Suit::Spade]);
+    }
+
+    #[test]
+    fn deals_every_method() {
+        let deck = crate::create_deck();
+        let hands = |method: &str, players| {
+            method
+                .parse::<DealMethod>()
+                .unwrap()
+                .deal(&deck[..10], players)
+                .into_iter()
+                .map(|hand| hand.iter().map(|card| card.rank.index()).collect())
+                .collect::<Vec<Vec<u8>>>()
+        };
+        assert_eq!(
+            hands("alternate", 3),
+            [vec![0, 3, 6, 9], vec![1, 4, 7], vec![2, 5, 8]]
+        );
+        assert_eq!(
+            hands("split", 3),
+            [vec![0, 1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]
+        );
+        assert_eq!(
+            hands("packets:3", 2),
+            [vec![0, 1, 2, 6, 7, 8], vec![3, 4, 5, 9]]
+        );
+        assert_eq!(
+            hands("handicap:2", 3),
+            [vec![0, 3], vec![1, 4, 6, 8], vec![2, 5, 7, 9]]
+        );
+
+        assert_eq!(DealMethod::classic(2), DealMethod::Split);
+        assert_eq!(DealMethod::classic(3), DealMethod::Alternate);
+        assert_eq!(
+            DealMethod::Split.deal(&deck, 2),
+            crate::split_deck(&deck).to_vec()
+        );
+    }
+
+    #[test]
+    fn reads_back_the_methods_it_prints() {
+        for method in [
+            DealMethod::Alternate,
+            DealMethod::Split,
+            DealMethod::Packets(4),
+            DealMethod::Handicap(3),
+        ] {
+            assert_eq!(method.to_string().parse::<DealMethod>(), Ok(method));
+        }
+        for invalid in ["packets", "packets:x", "split:2", "shuffle"] {
+            assert!(invalid.parse::<DealMethod>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn refuses_deals_that_leave_a_player_out() {
+        assert!(DealMethod::Packets(0).check(52, 2).is_err());
+        assert!(DealMethod::Packets(52).check(52, 2).is_err());
+        assert!(DealMethod::Packets(26).check(52, 2).is_ok());
+        assert!(DealMethod::Packets(18).check(52, 4).is_err());
+        assert!(DealMethod::Packets(17).check(52, 4).is_ok());
+        assert!(DealMethod::Handicap(26).check(52, 2).is_err());
+        assert!(DealMethod::Handicap(25).check(52, 2).is_ok());
+    }
+
+    #[test]
+    fn reads_deck_files() {
+        let spec = "# a short deck\nAS, KH QC # the court\n\n  *2♤ 10d\n"
+            .parse::<DeckSpec>()
+            .unwrap();
+        let expected = ["A♤", "K♡", "Q♧", "*2♤", "10♢"].map(|card| card.parse::<Card>().unwrap());
+        assert_eq!(spec, DeckSpec::Cards(expected.to_vec()));
+        assert_eq!(spec.len(), 5);
+
+        assert!("# nothing here\n".parse::<DeckSpec>().is_err());
+        assert!("AS ZZ".parse::<DeckSpec>().is_err());
+
+        let path = std::env::temp_dir().join(format!("bmn-deck-{}.txt", std::process::id()));
+        fs::write(&path, "2C 3C\n4C").unwrap();
+        let read = DeckSpec::from_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(read.unwrap().len(), 3);
+        assert!(DeckSpec::from_file(&path).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+// CRATES
+
+use crate::rules::Slap;
+use crate::{logging, Card, GameResult, Player};
+use serde::Serialize;
+use std::io::{self, Write};
+
+// STRUCTS + METHODS
+
+/// Everything that happens during a [`Game`](crate::Game), in the order it
+/// happens.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GameEvent<'a> {
+    CardPlayed {
+        player: Player,
+        card: Card,
+    },
+    /// `player` laid a penalty card and the next player owes `penalty` cards.
+    PenaltyStarted {
+        player: Player,
+        card: Card,
+        penalty: u8,
+    },
+    /// `player` slapped the pile in time to take it.
+    Slapped {
+        player: Player,
+        slap: Slap,
+    },
+    /// `player` slapped when nothing on the pile allowed it and put
+    /// `burned` under the pile.
+    FalseSlap {
+        player: Player,
+        burned: &'a [Card],
+    },
+    PileCollected {
+        player: Player,
+        cards: &'a [Card],
+    },
+    /// `player` had to play a card and had none left.
+    PlayerEliminated {
+        player: Player,
+    },
+    GameOver {
+        result: GameResult,
+    },
+}
+
+pub trait EventSink {
+    fn emit(&mut self, event: &GameEvent);
+}
+
+impl<S: EventSink + ?Sized> EventSink for &mut S {
+    fn emit(&mut self, event: &GameEvent) {
+        (**self).emit(event);
+    }
+}
+
+impl<S: EventSink + ?Sized> EventSink for Box<S> {
+    fn emit(&mut self, event: &GameEvent) {
+        (**self).emit(event);
+    }
+}
+
+/// Discards every event.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullSink;
+
+impl EventSink for NullSink {
+    fn emit(&mut self, _event: &GameEvent) {}
+}
+
+/// Prints events to stdout through the `logging` module.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrettySink;
+
+impl EventSink for PrettySink {
+    fn emit(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::CardPlayed { player, card } => logging::card_played(player, &card),
+            GameEvent::PenaltyStarted { card, penalty, .. } => {
+                logging::penalty_start(card, penalty)
+            }
+            GameEvent::Slapped { player, slap } => logging::slapped(player, slap),
+            GameEvent::FalseSlap { player, burned } => logging::false_slap(player, burned),
+            GameEvent::PileCollected { player, cards } => logging::cards_collected(player, cards),
+            GameEvent::PlayerEliminated { player } => logging::player_eliminated(player),
+            GameEvent::GameOver { result } => {
+                logging::game_over(result.outcome);
+                logging::game_length(&result);
+            }
+        }
+    }
+}
+
+/// Writes each event as one line of JSON.
+#[derive(Debug)]
+pub struct JsonLinesSink<W: Write> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> JsonLinesSink<W> {
+    pub fn new(writer: W) -> Self {
+        JsonLinesSink {
+            writer,
+            error: None,
+        }
+    }
+
+    /// Flush and hand back the writer, or the first error hit while writing.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write> EventSink for JsonLinesSink<W> {
+    fn emit(&mut self, event: &GameEvent) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let written = serde_json::to_writer(&mut self.writer, event)
+            .map_err(io::Error::from)
+            .and_then(|()| writeln!(self.writer));
+        if let Err(err) = written {
+            self.error = Some(err);
+        }
+    }
+}
@@ -0,0 +1,329 @@
+// CRATES
+
+use crate::player::MAX_PLAYERS;
+use crate::rank_game::Engine;
+use crate::ratscrew::{self, Agent, Slappers};
+use crate::rules::Rules;
+use crate::seed::ShuffleVersion;
+use crate::{DealMethod, DeckSpec, Distribution, First, Game, Player, Rank, Stats};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+
+// CONSTANTS
+
+/// Games each worker claims at a time.
+const CHUNK: u64 = 1024;
+/// The z-score for a 95% confidence interval.
+const Z_95: f64 = 1.96;
+
+// STRUCTS + METHODS
+
+#[derive(Debug, Clone)]
+pub struct ExperimentConfig {
+    pub rules: Rules,
+    pub deck: DeckSpec,
+    pub players: usize,
+    pub dealing: DealMethod,
+    pub first: First,
+    /// The simulated slappers, one per seat, if the rules allow slaps.
+    pub agents: Vec<Agent>,
+    pub shuffle: ShuffleVersion,
+    pub threads: usize,
+    pub games: u64,
+    /// Game `i` is dealt from `seed + i`, as in a batch run from `seed`.
+    pub seed: u64,
+}
+
+impl Default for ExperimentConfig {
+    fn default() -> Self {
+        ExperimentConfig {
+            rules: Rules::default(),
+            deck: DeckSpec::default(),
+            players: 2,
+            dealing: DealMethod::classic(2),
+            first: First::default(),
+            agents: Vec::new(),
+            shuffle: ShuffleVersion::default(),
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            games: 1_000_000,
+            seed: 0,
+        }
+    }
+}
+
+/// What an experiment found: every game together, and the games split by
+/// how many aces the first player, whoever led the first trick, was dealt.
+#[derive(Debug, Clone)]
+pub struct Summary {
+    pub stats: Stats,
+    /// Indexed by the number of aces in the leader's starting hand.
+    pub by_aces: Vec<Stats>,
+}
+
+impl Summary {
+    pub fn new(players: usize) -> Self {
+        Summary {
+            stats: Stats::new(players),
+            by_aces: Vec::new(),
+        }
+    }
+
+    pub fn merge(&mut self, other: &Summary) {
+        self.stats.merge(&other.stats);
+        for (aces, stats) in other.by_aces.iter().enumerate() {
+            self.aces(aces).merge(stats);
+        }
+    }
+
+    /// The share of games won by the player who led, with a 95% confidence
+    /// interval.
+    pub fn first_player_wins(&self) -> (f64, f64, f64) {
+        interval(&self.stats)
+    }
+
+    pub fn write_json(&self, config: &ExperimentConfig, mut writer: impl Write) -> io::Result<()> {
+        let (first_player_wins, low, high) = self.first_player_wins();
+        let summary = JsonSummary {
+            seed: config.seed,
+            shuffle: config.shuffle,
+            deck: config.deck.to_string(),
+            rules: config.rules,
+            players: config.players,
+            dealing: config.dealing,
+            first: config.first.to_string(),
+            games: self.stats.games(),
+            wins: Player::all(config.players)
+                .map(|player| self.stats.wins(player))
+                .collect(),
+            infinite: self.stats.infinite(),
+            first_player_wins,
+            first_player_wins_95: [low, high],
+            cards_played: JsonDistribution::new(self.stats.cards_played()),
+            tricks: JsonDistribution::new(self.stats.tricks()),
+            by_aces: self
+                .by_aces
+                .iter()
+                .enumerate()
+                .filter(|(_, stats)| stats.games() > 0)
+                .map(|(aces, stats)| {
+                    let (first_player_wins, low, high) = interval(stats);
+                    JsonAces {
+                        aces,
+                        games: stats.games(),
+                        first_player_wins,
+                        first_player_wins_95: [low, high],
+                        mean_cards_played: stats.cards_played().mean(),
+                        mean_tricks: stats.tricks().mean(),
+                    }
+                })
+                .collect(),
+        };
+
+        serde_json::to_writer(&mut writer, &summary)?;
+        writeln!(writer)?;
+        writer.flush()
+    }
+
+    /// Write the summary as `measure,key,value` rows: the win counts, every
+    /// game length seen and, per ace count, games and first-player wins.
+    pub fn write_csv(&self, config: &ExperimentConfig, mut writer: impl Write) -> io::Result<()> {
+        let stats = &self.stats;
+        let (first_player_wins, low, high) = self.first_player_wins();
+
+        writeln!(writer, "measure,key,value")?;
+        writeln!(writer, "seed,,{}", config.seed)?;
+        writeln!(writer, "dealing,,{}", config.dealing)?;
+        writeln!(writer, "first,,{}", config.first)?;
+        writeln!(writer, "games,,{}", stats.games())?;
+        for player in Player::all(stats.players()) {
+            writeln!(writer, "wins,{},{}", player.number(), stats.wins(player))?;
+        }
+        writeln!(writer, "infinite,,{}", stats.infinite())?;
+        writeln!(writer, "first_player_wins,,{}", first_player_wins)?;
+        writeln!(writer, "first_player_wins_95,low,{}", low)?;
+        writeln!(writer, "first_player_wins_95,high,{}", high)?;
+
+        for (measure, lengths) in [
+            ("cards_played", stats.cards_played()),
+            ("tricks", stats.tricks()),
+        ] {
+            for (length, games) in lengths.histogram(1) {
+                if games > 0 {
+                    writeln!(writer, "{},{},{}", measure, length, games)?;
+                }
+            }
+        }
+
+        for (aces, stats) in self.by_aces.iter().enumerate() {
+            if stats.games() == 0 {
+                continue;
+            }
+            writeln!(writer, "aces_games,{},{}", aces, stats.games())?;
+            writeln!(
+                writer,
+                "aces_first_player_wins,{},{}",
+                aces,
+                stats.leader_wins()
+            )?;
+            writeln!(
+                writer,
+                "aces_mean_cards_played,{},{}",
+                aces,
+                stats.cards_played().mean()
+            )?;
+        }
+        writer.flush()
+    }
+
+    fn aces(&mut self, aces: usize) -> &mut Stats {
+        if self.by_aces.len() <= aces {
+            self.by_aces
+                .resize(aces + 1, Stats::new(self.stats.players()));
+        }
+        &mut self.by_aces[aces]
+    }
+}
+
+#[derive(Serialize)]
+struct JsonSummary {
+    seed: u64,
+    shuffle: ShuffleVersion,
+    deck: String,
+    rules: Rules,
+    players: usize,
+    dealing: DealMethod,
+    first: String,
+    games: u64,
+    wins: Vec<u64>,
+    infinite: u64,
+    first_player_wins: f64,
+    first_player_wins_95: [f64; 2],
+    cards_played: JsonDistribution,
+    tricks: JsonDistribution,
+    by_aces: Vec<JsonAces>,
+}
+
+#[derive(Serialize)]
+struct JsonDistribution {
+    mean: f64,
+    median: usize,
+    max: usize,
+    /// `[length, games]` for every length seen.
+    counts: Vec<(usize, u64)>,
+}
+
+impl JsonDistribution {
+    fn new(lengths: &Distribution) -> Self {
+        JsonDistribution {
+            mean: lengths.mean(),
+            median: lengths.median(),
+            max: lengths.max(),
+            counts: lengths
+                .histogram(1)
+                .into_iter()
+                .filter(|&(_, games)| games > 0)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonAces {
+    aces: usize,
+    games: u64,
+    first_player_wins: f64,
+    first_player_wins_95: [f64; 2],
+    mean_cards_played: f64,
+    mean_tricks: f64,
+}
+
+// HELPER FUNCTIONS
+
+/// Play `config.games` seeded games across `config.threads` workers without
+/// any logging. The summary depends only on the config, however the games
+/// fall across threads.
+pub fn run(config: &ExperimentConfig) -> Summary {
+    let next = AtomicU64::new(0);
+    let mut summary = Summary::new(config.players);
+
+    thread::scope(|scope| {
+        let workers = (0..config.threads.max(1))
+            .map(|_| scope.spawn(|| work(config, &next)))
+            .collect::<Vec<_>>();
+        for worker in workers {
+            summary.merge(&worker.join().unwrap());
+        }
+    });
+
+    summary
+}
+
+/// Play games a chunk at a time until every game has been claimed. Games
+/// without slaps only need penalty values, so they go through the rank-only
+/// engine.
+fn work(config: &ExperimentConfig, next: &AtomicU64) -> Summary {
+    let mut summary = Summary::new(config.players);
+    let cards = config.deck.cards();
+    let mut engine = Engine::new();
+    let mut penalties = Vec::with_capacity(cards.len());
+
+    loop {
+        let start = next.fetch_add(CHUNK, Ordering::Relaxed);
+        if start >= config.games {
+            break summary;
+        }
+
+        for game_idx in start..(start + CHUNK).min(config.games) {
+            let seed = config.seed.wrapping_add(game_idx);
+            let deck = config.shuffle.shuffle(seed, cards.clone());
+            let decks = config.dealing.deal(&deck, config.players);
+            let first = config
+                .first
+                .pick(game_idx, seed, config.shuffle, config.players);
+            let aces = decks[first.index()]
+                .iter()
+                .filter(|card| card.rank == Rank::Ace)
+                .count();
+
+            let result = if config.rules.slaps.enabled() {
+                let mut game = Game::new(decks).with_rules(config.rules).with_first(first);
+                let mut slappers = Slappers::new(&config.agents, seed, config.shuffle);
+                ratscrew::play(&mut game, &mut slappers)
+            } else {
+                penalties.clear();
+                penalties.extend(
+                    decks
+                        .iter()
+                        .flatten()
+                        .map(|card| config.rules.penalty(card.rank).unwrap_or(0)),
+                );
+                let mut hands: [&[u8]; MAX_PLAYERS] = [&[]; MAX_PLAYERS];
+                let mut rest = penalties.as_slice();
+                for (slot, deck) in hands.iter_mut().zip(&decks) {
+                    (*slot, rest) = rest.split_at(deck.len());
+                }
+                engine.play(&hands[..decks.len()], &config.rules, first)
+            };
+
+            summary.stats.record(&result, first);
+            summary.aces(aces).record(&result, first);
+        }
+    }
+}
+
+/// The first player's win rate in `stats` with the bounds of its 95%
+/// confidence interval, by the Wilson score method.
+fn interval(stats: &Stats) -> (f64, f64, f64) {
+    let n = stats.games() as f64;
+    let p = stats.leader_win_rate();
+    if n == 0.0 {
+        return (0.0, 0.0, 0.0);
+    }
+
+    let z2 = Z_95 * Z_95;
+    let centre = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let margin = Z_95 / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    (p, centre - margin, centre + margin)
+}
@@ -0,0 +1,517 @@
+// CRATES
+
+use crate::events::{EventSink, GameEvent, NullSink};
+use crate::player::{MAX_PLAYERS, MIN_PLAYERS};
+use crate::rules::{Leader, OutOfCards, Rules, Slap};
+use crate::{Card, Player};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+// CONSTANTS
+
+const HAND_SEPARATOR: u8 = u8::MAX;
+
+// STRUCTS + METHODS
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameOutcome {
+    Winner(Player),
+    /// The position at trick `cycle_start` came round again after
+    /// `cycle_length` more tricks, so the game can never end.
+    Infinite {
+        cycle_start: usize,
+        cycle_length: usize,
+    },
+}
+
+/// How a game finished, measured the way the search literature measures it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameResult {
+    pub outcome: GameOutcome,
+    /// Every card laid on the central pile, including cards paid during
+    /// penalty phases.
+    pub cards_played: usize,
+    /// The number of times a player collected the central pile.
+    pub tricks: usize,
+    /// The number of penalty cards played, each of which starts a fresh
+    /// countdown for the next player.
+    pub penalty_phases: usize,
+}
+
+impl GameResult {
+    pub fn winner(&self) -> Option<Player> {
+        match self.outcome {
+            GameOutcome::Winner(player) => Some(player),
+            GameOutcome::Infinite { .. } => None,
+        }
+    }
+}
+
+/// A game in progress between two to eight players, seated in the order
+/// their hands are given. Everything that happens is reported to the event
+/// sink `S`, which discards it by default.
+pub struct Game<S: EventSink = NullSink> {
+    decks: Vec<VecDeque<Card>>,
+    live: Vec<bool>,
+    central_pile: Vec<Card>,
+    current_player: Player,
+    cards_played: usize,
+    tricks: usize,
+    penalty_phases: usize,
+    penalty: Option<Penalty>,
+    seen_states: HashMap<Vec<u8>, usize>,
+    outcome: Option<GameOutcome>,
+    rules: Rules,
+    sink: S,
+}
+
+/// A penalty being paid: `payer` owes `required - paid` more cards to
+/// `initiator`, who laid the last penalty card.
+#[derive(Debug, Clone, Copy)]
+struct Penalty {
+    /// The player who opened the phase, who leads again under
+    /// [`Leader::Legacy`].
+    opener: Player,
+    initiator: Player,
+    required: u8,
+    paid: u8,
+}
+
+impl Game {
+    pub fn new(decks: impl Into<Vec<VecDeque<Card>>>) -> Self {
+        let decks = decks.into();
+        assert!(
+            (MIN_PLAYERS..=MAX_PLAYERS).contains(&decks.len()),
+            "a game needs {} to {} players, not {}",
+            MIN_PLAYERS,
+            MAX_PLAYERS,
+            decks.len()
+        );
+        assert!(
+            decks.iter().filter(|deck| !deck.is_empty()).count() >= 2,
+            "a game needs at least two players holding cards"
+        );
+
+        let mut game = Game {
+            live: vec![true; decks.len()],
+            decks,
+            central_pile: Vec::new(),
+            current_player: Player::ONE,
+            cards_played: 0,
+            tricks: 0,
+            penalty_phases: 0,
+            penalty: None,
+            seen_states: HashMap::new(),
+            outcome: None,
+            rules: Rules::default(),
+            sink: NullSink,
+        };
+        game.seen_states.insert(game.state_key(), 0);
+        game
+    }
+}
+
+impl<S: EventSink> Game<S> {
+    /// Play under `rules`. Call it before play starts.
+    pub fn with_rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self.seen_states.clear();
+        self.seen_states.insert(self.state_key(), 0);
+        self
+    }
+
+    /// Have `player` lead the first trick instead of player one. Call it
+    /// before play starts.
+    pub fn with_first(mut self, player: Player) -> Self {
+        assert!(
+            player.index() < self.players(),
+            "player {} is not at a table of {}",
+            player.number(),
+            self.players()
+        );
+        self.current_player = player;
+        self.seen_states.clear();
+        self.seen_states.insert(self.state_key(), 0);
+        self
+    }
+
+    pub fn with_sink<T: EventSink>(self, sink: T) -> Game<T> {
+        Game {
+            decks: self.decks,
+            live: self.live,
+            central_pile: self.central_pile,
+            current_player: self.current_player,
+            cards_played: self.cards_played,
+            tricks: self.tricks,
+            penalty_phases: self.penalty_phases,
+            penalty: self.penalty,
+            seen_states: self.seen_states,
+            outcome: self.outcome,
+            rules: self.rules,
+            sink,
+        }
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    pub fn sink_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    pub fn into_sink(self) -> S {
+        self.sink
+    }
+
+    pub fn players(&self) -> usize {
+        self.decks.len()
+    }
+
+    pub fn decks(&self) -> &[VecDeque<Card>] {
+        &self.decks
+    }
+
+    /// Whether `player` is still in the game. Players drop out when they have
+    /// to play or pay a card and have none left.
+    pub fn is_live(&self, player: Player) -> bool {
+        self.live[player.index()]
+    }
+
+    pub fn central_pile(&self) -> &[Card] {
+        &self.central_pile
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn current_player(&self) -> Player {
+        self.current_player
+    }
+
+    pub fn cards_played(&self) -> usize {
+        self.cards_played
+    }
+
+    pub fn tricks(&self) -> usize {
+        self.tricks
+    }
+
+    pub fn penalty_phases(&self) -> usize {
+        self.penalty_phases
+    }
+
+    pub fn result(&self) -> Option<GameResult> {
+        self.outcome.map(|outcome| GameResult {
+            outcome,
+            cards_played: self.cards_played,
+            tricks: self.tricks,
+            penalty_phases: self.penalty_phases,
+        })
+    }
+
+    /// The penalty being paid, if any: who laid the penalty card and how
+    /// many more cards the current player owes them.
+    pub fn penalty(&self) -> Option<(Player, u8)> {
+        self.penalty
+            .map(|penalty| (penalty.initiator, penalty.required - penalty.paid))
+    }
+
+    /// The slap the top of the central pile allows under the rules, if any.
+    pub fn slappable(&self) -> Option<Slap> {
+        self.rules.slaps.check(&self.central_pile)
+    }
+
+    /// Play a single card for the current player, whether leading or paying
+    /// a penalty. Returns the result once the game is over or has been found
+    /// to repeat forever.
+    pub fn step(&mut self) -> Option<GameResult> {
+        if self.outcome.is_some() {
+            return self.result();
+        }
+
+        let player = self.current_player;
+        let Some(mut penalty) = self.penalty else {
+            return self.lead(player);
+        };
+
+        let Some(card) = self.decks[player.index()].pop_front() else {
+            self.eliminate(player);
+            if self.rules.out_of_cards == OutOfCards::Forfeit {
+                return self.collect(penalty.initiator, None);
+            }
+            return self.pass_penalty(player, penalty);
+        };
+        self.play_card(player, card);
+
+        if let Some(required) = self.rules.penalty(card.rank) {
+            self.start_penalty(player, card, required);
+            penalty.initiator = player;
+            penalty.required = required;
+            penalty.paid = 0;
+            return self.pass_penalty(player, penalty);
+        }
+
+        penalty.paid += 1;
+        if penalty.paid < penalty.required {
+            self.penalty = Some(penalty);
+            return None;
+        }
+        self.collect(penalty.initiator, None)
+    }
+
+    /// Slap the central pile for `player`. If the top of the pile allows a
+    /// slap they take the pile and lead next; otherwise they burn cards from
+    /// their hand under the pile. Players who are out cannot slap.
+    pub fn slap(&mut self, player: Player) -> Option<GameResult> {
+        if self.outcome.is_some() || !self.live[player.index()] || !self.rules.slaps.enabled() {
+            return self.result();
+        }
+
+        // Positions seen before a slap say nothing about where play goes
+        // after it, so only a repeat with no slap in between is a cycle.
+        self.seen_states.clear();
+
+        if let Some(slap) = self.slappable() {
+            self.sink.emit(&GameEvent::Slapped { player, slap });
+            return self.collect(player, Some(player));
+        }
+
+        let burn = (self.rules.slaps.burn as usize).min(self.decks[player.index()].len());
+        let burned = self.decks[player.index()].drain(..burn).collect::<Vec<_>>();
+        self.sink.emit(&GameEvent::FalseSlap {
+            player,
+            burned: &burned,
+        });
+        self.central_pile.splice(0..0, burned);
+        None
+    }
+
+    pub fn run(&mut self) -> GameResult {
+        loop {
+            if let Some(result) = self.step() {
+                break result;
+            }
+        }
+    }
+
+    /// The next player clockwise from `player` who is still in the game.
+    fn next_live(&self, player: Player) -> Player {
+        let mut next = player.next(self.players());
+        while !self.live[next.index()] && next != player {
+            next = next.next(self.players());
+        }
+        next
+    }
+
+    fn eliminate(&mut self, player: Player) {
+        self.live[player.index()] = false;
+        self.sink.emit(&GameEvent::PlayerEliminated { player });
+    }
+
+    /// Knock out everyone left without cards, ending the game if only one
+    /// player remains, and pass the lead on if its holder went out.
+    fn settle(&mut self) -> Option<GameResult> {
+        for player in Player::all(self.players()) {
+            if self.live[player.index()] && self.decks[player.index()].is_empty() {
+                self.eliminate(player);
+            }
+        }
+
+        // Everyone out at once leaves the pile with the player to move.
+        let mut live = Player::all(self.players()).filter(|player| self.live[player.index()]);
+        match (live.next(), live.next()) {
+            (Some(winner), None) => return self.conclude(GameOutcome::Winner(winner)),
+            (None, _) => return self.conclude(GameOutcome::Winner(self.current_player)),
+            _ => {}
+        }
+
+        if !self.live[self.current_player.index()] {
+            self.current_player = self.next_live(self.current_player);
+        }
+        self.result()
+    }
+
+    fn conclude(&mut self, outcome: GameOutcome) -> Option<GameResult> {
+        self.outcome = Some(outcome);
+        let result = self.result();
+
+        if let Some(result) = result {
+            self.sink.emit(&GameEvent::GameOver { result });
+        }
+        result
+    }
+
+    /// Record the position after a pile has been collected, returning the
+    /// cycle if the same position has been reached before, with no slap
+    /// since.
+    fn detect_cycle(&mut self) -> Option<GameOutcome> {
+        let key = self.state_key();
+        if let Some(&cycle_start) = self.seen_states.get(&key) {
+            return Some(GameOutcome::Infinite {
+                cycle_start,
+                cycle_length: self.tricks - cycle_start,
+            });
+        }
+
+        self.seen_states.insert(key, self.tricks);
+        None
+    }
+
+    /// The position as far as play can tell: suits never matter, and
+    /// without slaps neither does anything but a card's penalty, so a deal
+    /// is found to cycle as soon as its penalty values repeat.
+    fn state_key(&self) -> Vec<u8> {
+        let slaps = self.rules.slaps.enabled();
+        let encode = |card: &Card| match slaps {
+            true => card.rank.index(),
+            false => self.rules.penalty(card.rank).unwrap_or(0),
+        };
+
+        let cards = self.decks.iter().map(|deck| deck.len()).sum::<usize>();
+        let mut key = Vec::with_capacity(cards + self.players() + 1);
+        key.push(self.current_player.index() as u8);
+        for deck in &self.decks {
+            key.extend(deck.iter().map(encode));
+            key.push(HAND_SEPARATOR);
+        }
+        key.extend(self.central_pile.iter().map(encode));
+        key
+    }
+
+    fn play_card(&mut self, player: Player, card: Card) {
+        self.central_pile.push(card);
+        self.cards_played += 1;
+        self.sink.emit(&GameEvent::CardPlayed { player, card });
+    }
+
+    fn start_penalty(&mut self, player: Player, card: Card, penalty: u8) {
+        self.penalty_phases += 1;
+        self.sink.emit(&GameEvent::PenaltyStarted {
+            player,
+            card,
+            penalty,
+        });
+    }
+
+    /// Lay a card for `player`, who is not paying a penalty, opening a
+    /// penalty phase if it is a penalty card.
+    fn lead(&mut self, player: Player) -> Option<GameResult> {
+        let Some(card) = self.decks[player.index()].pop_front() else {
+            return self.settle();
+        };
+        self.play_card(player, card);
+
+        let Some(required) = self.rules.penalty(card.rank) else {
+            self.current_player = self.next_live(player);
+            return self.settle();
+        };
+        self.start_penalty(player, card, required);
+        self.pass_penalty(
+            player,
+            Penalty {
+                opener: player,
+                initiator: player,
+                required,
+                paid: 0,
+            },
+        )
+    }
+
+    /// Hand the penalty on to the next live player after `player`. If that
+    /// is the player who laid the penalty card, everyone else is out.
+    fn pass_penalty(&mut self, player: Player, penalty: Penalty) -> Option<GameResult> {
+        self.current_player = self.next_live(player);
+        if self.current_player == penalty.initiator {
+            self.penalty = None;
+            return self.conclude(GameOutcome::Winner(penalty.initiator));
+        }
+        self.penalty = Some(penalty);
+        None
+    }
+
+    /// Give the central pile to `collector`, ending any penalty phase. The
+    /// next trick is led by `leader` if given, otherwise as the rules say.
+    fn collect(&mut self, collector: Player, leader: Option<Player>) -> Option<GameResult> {
+        self.sink.emit(&GameEvent::PileCollected {
+            player: collector,
+            cards: &self.central_pile,
+        });
+        self.decks[collector.index()].extend(self.central_pile.drain(..));
+
+        let opener = self.penalty.take().map(|penalty| penalty.opener);
+        self.current_player = match (leader, opener, self.rules.leader) {
+            (Some(leader), _, _) => leader,
+            (None, Some(opener), Leader::Legacy) => opener,
+            _ => collector,
+        };
+
+        self.tricks += 1;
+        if let Some(result) = self.settle() {
+            return Some(result);
+        }
+        if let Some(cycle) = self.detect_cycle() {
+            return self.conclude(cycle);
+        }
+        self.settle()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ratscrew::{self, Agent, Slappers};
+    use crate::rules::SlapRules;
+    use crate::seed::ShuffleVersion;
+    use crate::Deal;
+
+    /// The first deal found to play forever.
+    const INFINITE: &str = "---K---Q-KQAJ-----AAJ--J--/----------Q----KQ-J-----KA";
+
+    #[test]
+    #[should_panic(expected = "at least two players holding cards")]
+    fn needs_two_hands_of_cards() {
+        Game::new(vec![VecDeque::from(crate::create_deck()), VecDeque::new()]);
+    }
+
+    #[test]
+    fn stops_ratscrew_games_nobody_slaps() {
+        let rules = Rules {
+            slaps: SlapRules::ratscrew(),
+            ..Rules::default()
+        };
+        let hands = INFINITE
+            .parse::<Deal>()
+            .unwrap()
+            .to_cards(&crate::create_deck(), &rules)
+            .unwrap();
+        let idle = Agent {
+            accuracy: 0.0,
+            false_slaps: 0.0,
+            ..Agent::default()
+        };
+        let mut slappers = Slappers::new(&[idle; 2], 0, ShuffleVersion::default());
+        let mut game = Game::new(hands).with_rules(rules);
+        assert_eq!(ratscrew::play(&mut game, &mut slappers).winner(), None);
+    }
+
+    #[test]
+    fn finds_the_published_cycle() {
+        let rules = Rules::default();
+        let hands = INFINITE
+            .parse::<Deal>()
+            .unwrap()
+            .to_cards(&crate::create_deck(), &rules)
+            .unwrap();
+        let result = Game::new(hands).run();
+        assert_eq!(
+            result.outcome,
+            GameOutcome::Infinite {
+                cycle_start: 4,
+                cycle_length: 62
+            }
+        );
+        assert_eq!(result.cards_played, 474);
+    }
+}
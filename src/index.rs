@@ -0,0 +1,387 @@
+// CRATES
+
+use crate::rules::Rules;
+use crate::{Deal, DealMethod, DeckSpec};
+use num_bigint::BigUint;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+// STRUCTS + METHODS
+
+/// A deal's place in the order of every rank-only deal of its deck,
+/// counting from zero. Even a single standard pack has more deals than 64
+/// bits can count, so it is saved as a string of digits.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct DealIndex(BigUint);
+
+impl DealIndex {
+    pub fn new(index: BigUint) -> Self {
+        DealIndex(index)
+    }
+
+    pub fn value(&self) -> &BigUint {
+        &self.0
+    }
+}
+
+impl From<u64> for DealIndex {
+    fn from(index: u64) -> Self {
+        DealIndex(BigUint::from(index))
+    }
+}
+
+impl fmt::Display for DealIndex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for DealIndex {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim()
+            .parse()
+            .map(DealIndex)
+            .map_err(|_| format!("invalid deal index '{}', expected a whole number", s))
+    }
+}
+
+impl Serialize for DealIndex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for DealIndex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Every rank-only deal of a deck into hands of fixed sizes, numbered by
+/// reading the hands one after another and putting the deals in dictionary
+/// order, number cards before jacks, jacks before queens and so on. A
+/// standard deck split in two has 52!/(36!·4!⁴) deals: index zero puts every
+/// number card first and the aces last, and the last index the other way
+/// round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DealSpace {
+    /// How many cards of each penalty value the deck holds.
+    counts: Vec<usize>,
+    hand_sizes: Vec<usize>,
+}
+
+impl DealSpace {
+    /// The deals of the cards with these penalty values into hands of
+    /// `hand_sizes`, which must hold every card between them.
+    pub fn new(penalties: &[u8], hand_sizes: Vec<usize>) -> Result<Self, String> {
+        if hand_sizes.iter().sum::<usize>() != penalties.len() {
+            return Err(format!(
+                "hands of {} cards cannot hold a deck of {}",
+                hand_sizes.iter().sum::<usize>(),
+                penalties.len()
+            ));
+        }
+
+        let mut counts = vec![0; penalties.iter().max().map_or(0, |&max| max as usize + 1)];
+        for &penalty in penalties {
+            counts[penalty as usize] += 1;
+        }
+        Ok(DealSpace { counts, hand_sizes })
+    }
+
+    /// The deals of `deck` under `rules` to `players`, in hands the sizes
+    /// `dealing` gives them.
+    pub fn of_deck(deck: &DeckSpec, rules: &Rules, dealing: DealMethod, players: usize) -> Self {
+        let cards = deck.cards();
+        let penalties = cards
+            .iter()
+            .map(|card| rules.penalty(card.rank).unwrap_or(0))
+            .collect::<Vec<_>>();
+        let hand_sizes = dealing
+            .deal(&cards, players)
+            .iter()
+            .map(|hand| hand.len())
+            .collect();
+        DealSpace::new(&penalties, hand_sizes).expect("a deal holds the whole deck")
+    }
+
+    /// The deals of the same cards as `deal` into hands of the same sizes.
+    pub fn of_deal(deal: &Deal) -> Self {
+        let hand_sizes = deal.hands().iter().map(Vec::len).collect();
+        DealSpace::new(&deal.hands().concat(), hand_sizes).expect("a deal holds its own cards")
+    }
+
+    pub fn hand_sizes(&self) -> &[usize] {
+        &self.hand_sizes
+    }
+
+    /// How many deals there are: the multinomial coefficient of the deck's
+    /// penalty counts.
+    pub fn size(&self) -> BigUint {
+        let mut size = BigUint::from(1u8);
+        let mut cards = 0usize;
+        for &count in &self.counts {
+            for taken in 1..=count {
+                cards += 1;
+                size = size * cards / taken;
+            }
+        }
+        size
+    }
+
+    /// The index of `deal`, which must hold exactly this space's cards in
+    /// hands of its sizes.
+    pub fn rank(&self, deal: &Deal) -> Result<DealIndex, String> {
+        let sizes = deal.hands().iter().map(Vec::len).collect::<Vec<_>>();
+        if sizes != self.hand_sizes {
+            return Err(format!(
+                "the deal has hands of {} cards, not {}",
+                print_sizes(&sizes),
+                print_sizes(&self.hand_sizes)
+            ));
+        }
+
+        let mut counts = self.counts.clone();
+        let mut cards = self.hand_sizes.iter().sum::<usize>();
+        // The number of ways to arrange the cards not yet placed.
+        let mut arrangements = self.size();
+        let mut index = BigUint::default();
+
+        for &penalty in deal.hands().iter().flatten() {
+            let penalty = penalty as usize;
+            if counts.get(penalty).is_none_or(|&count| count == 0) {
+                return Err(format!(
+                    "the deal holds more cards of penalty {} than the deck",
+                    penalty
+                ));
+            }
+            for &count in &counts[..penalty] {
+                index += &arrangements * count / cards;
+            }
+            arrangements = arrangements * counts[penalty] / cards;
+            counts[penalty] -= 1;
+            cards -= 1;
+        }
+        Ok(DealIndex(index))
+    }
+
+    /// The deal at `index`.
+    pub fn unrank(&self, index: &DealIndex) -> Result<Deal, String> {
+        let size = self.size();
+        if index.0 >= size {
+            return Err(format!(
+                "deal index {} is out of range: there are {} deals",
+                index, size
+            ));
+        }
+
+        let mut counts = self.counts.clone();
+        let mut cards = self.hand_sizes.iter().sum::<usize>();
+        let mut arrangements = size;
+        let mut index = index.0.clone();
+        let mut sequence = Vec::with_capacity(cards);
+
+        while cards > 0 {
+            for (penalty, count) in counts.iter_mut().enumerate() {
+                let starting_with = &arrangements * *count / cards;
+                if index < starting_with {
+                    sequence.push(penalty as u8);
+                    arrangements = starting_with;
+                    *count -= 1;
+                    break;
+                }
+                index -= starting_with;
+            }
+            cards -= 1;
+        }
+        Ok(self.split(&sequence))
+    }
+
+    /// Every deal from `start` on, in order, each with its index. Stepping
+    /// from one deal to the next takes no big-number division, so this is
+    /// much faster than unranking each index in turn.
+    pub fn deals_from(&self, start: &DealIndex) -> Result<Deals<'_>, String> {
+        let deal = self.unrank(start)?;
+        Ok(Deals {
+            space: self,
+            index: start.clone(),
+            sequence: Some(deal.hands().concat()),
+        })
+    }
+
+    fn split(&self, sequence: &[u8]) -> Deal {
+        let mut rest = sequence;
+        Deal::new(
+            self.hand_sizes
+                .iter()
+                .map(|&size| {
+                    let (hand, after) = rest.split_at(size);
+                    rest = after;
+                    hand.to_vec()
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+/// The deals of a [`DealSpace`] from some index on.
+#[derive(Debug, Clone)]
+pub struct Deals<'a> {
+    space: &'a DealSpace,
+    index: DealIndex,
+    /// The cards of the next deal read hand after hand, or `None` once the
+    /// last deal has been given out.
+    sequence: Option<Vec<u8>>,
+}
+
+impl Iterator for Deals<'_> {
+    type Item = (DealIndex, Deal);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let sequence = self.sequence.as_mut()?;
+        let item = (self.index.clone(), self.space.split(sequence));
+
+        if next_arrangement(sequence) {
+            self.index.0 += 1u8;
+        } else {
+            self.sequence = None;
+        }
+        Some(item)
+    }
+}
+
+// HELPER FUNCTIONS
+
+/// Step `cards` to the next arrangement in dictionary order, returning
+/// `false` if it was already the last.
+fn next_arrangement(cards: &mut [u8]) -> bool {
+    let Some(pivot) = cards.windows(2).rposition(|pair| pair[0] < pair[1]) else {
+        return false;
+    };
+    let successor = cards
+        .iter()
+        .rposition(|&card| card > cards[pivot])
+        .expect("a later card is larger than the pivot");
+    cards.swap(pivot, successor);
+    cards[pivot + 1..].reverse();
+    true
+}
+
+fn print_sizes(sizes: &[usize]) -> String {
+    sizes
+        .iter()
+        .map(|size| size.to_string())
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seed::ShuffleVersion;
+
+    fn standard() -> DealSpace {
+        DealSpace::of_deck(
+            &DeckSpec::default(),
+            &Rules::default(),
+            DealMethod::classic(2),
+            2,
+        )
+    }
+
+    #[test]
+    fn counts_standard_deals() {
+        // 52! / (36! 4! 4! 4! 4!)
+        let mut expected = BigUint::from(1u8);
+        for n in 37..=52u32 {
+            expected *= n;
+        }
+        expected /= 24u32.pow(4);
+        assert_eq!(standard().size(), expected);
+        assert_eq!(expected.to_string(), "653534134886878245000");
+    }
+
+    #[test]
+    fn unranks_what_it_ranks() {
+        let space = standard();
+        for seed in 0..200 {
+            let cards = ShuffleVersion::default().shuffle(seed, DeckSpec::default().cards());
+            let hands = DealMethod::classic(2).deal(&cards, 2);
+            let deal = Deal::from_cards(&hands, &Rules::default());
+            let index = space.rank(&deal).unwrap();
+            assert_eq!(space.unrank(&index).unwrap(), deal);
+        }
+    }
+
+    #[test]
+    fn ranks_what_it_unranks() {
+        let space = standard();
+        let last = space.size() - 1u8;
+        for index in [
+            BigUint::from(0u8),
+            BigUint::from(1u8),
+            &last / 2u8,
+            last.clone(),
+        ] {
+            let index = DealIndex::new(index);
+            let deal = space.unrank(&index).unwrap();
+            assert_eq!(space.rank(&deal).unwrap(), index);
+        }
+
+        let first = space.unrank(&DealIndex::from(0)).unwrap();
+        assert_eq!(
+            first.to_string(),
+            "--------------------------/----------JJJJQQQQKKKKAAAA"
+        );
+    }
+
+    #[test]
+    fn rejects_indexes_past_the_end() {
+        let space = standard();
+        assert!(space.unrank(&DealIndex::new(space.size())).is_err());
+        assert!(space.deals_from(&DealIndex::new(space.size())).is_err());
+    }
+
+    #[test]
+    fn steps_through_deals_in_order() {
+        let space = standard();
+        let start = BigUint::from(10u64.pow(12));
+        for (k, (index, deal)) in space
+            .deals_from(&DealIndex::new(start.clone()))
+            .unwrap()
+            .take(50)
+            .enumerate()
+        {
+            let expected = DealIndex::new(&start + k);
+            assert_eq!(index, expected);
+            assert_eq!(deal, space.unrank(&expected).unwrap());
+        }
+
+        let last = DealIndex::new(space.size() - 1u8);
+        let mut deals = space.deals_from(&last).unwrap();
+        assert_eq!(deals.next().map(|(index, _)| index), Some(last));
+        assert!(deals.next().is_none());
+    }
+
+    #[test]
+    fn covers_small_spaces_exactly() {
+        // Two jacks, a queen and four number cards split four and three.
+        let space = DealSpace::new(&[0, 0, 0, 0, 1, 1, 2], vec![4, 3]).unwrap();
+        assert_eq!(space.size(), BigUint::from(105u8));
+
+        let deals = space
+            .deals_from(&DealIndex::default())
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(deals.len(), 105);
+        for (index, deal) in &deals {
+            assert_eq!(&space.rank(deal).unwrap(), index);
+        }
+        assert!(deals.windows(2).all(|pair| pair[0].1 != pair[1].1));
+    }
+}
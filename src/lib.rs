@@ -0,0 +1,36 @@
+//! A seeded simulation of Beggar-my-neighbour.
+//!
+//! The [`Game`] engine plays two to eight hands against each other until one
+//! player holds every card, and returns a [`GameResult`] describing the
+//! finish. Games that return to an earlier position are stopped and reported
+//! as infinite.
+
+// MODULES
+
+pub mod bench;
+mod card;
+mod deal;
+pub mod deck;
+pub mod events;
+pub mod experiment;
+mod game;
+pub mod index;
+pub mod logging;
+pub mod player;
+pub mod rank_game;
+pub mod ratscrew;
+pub mod rules;
+pub mod search;
+pub mod seed;
+mod stats;
+pub mod store;
+pub mod transcript;
+
+// EXPORTS
+
+pub use card::{create_deck, deal_round_robin, print_cards, split_deck, Card, Rank, Suit};
+pub use deal::{CanonicalDeal, Deal, Equivalence, Hands};
+pub use deck::{DealMethod, DeckSpec, Pack};
+pub use game::{Game, GameOutcome, GameResult};
+pub use player::{First, Player};
+pub use stats::{Distribution, Orientations, Stats};
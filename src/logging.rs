@@ -0,0 +1,355 @@
+// CRATES
+
+use crate::bench::{BenchConfig, BenchReport};
+use crate::experiment::{ExperimentConfig, Summary};
+use crate::index::DealIndex;
+use crate::ratscrew::Agent;
+use crate::rules::{Rules, Slap};
+use crate::search::{Entry, Leaderboard, SearchConfig};
+use crate::seed::ShuffleVersion;
+use crate::{
+    print_cards, Card, Deal, DealMethod, DeckSpec, Distribution, Equivalence, GameOutcome,
+    GameResult, Orientations, Player, Stats,
+};
+use num_bigint::BigUint;
+use std::path::Path;
+use std::time::Duration;
+
+// LOGGING
+
+pub fn seed(seed: u64, shuffle: ShuffleVersion) {
+    println!("SEED: {} (shuffle {})", seed, shuffle);
+}
+
+pub fn deal_index(index: &DealIndex, deals: &BigUint) {
+    println!("DEAL INDEX: {} of {}", index, deals);
+}
+
+pub fn replay(id: u64) {
+    println!("REPLAY: #{}", id);
+}
+
+pub fn saved(id: u64, path: &Path) {
+    println!("\nSAVED: #{} → {}", id, path.display());
+}
+
+pub fn duplicate(id: u64, path: &Path) {
+    println!(
+        "\nNOT SAVED: the same game as #{} in {}",
+        id,
+        path.display()
+    );
+}
+
+pub fn duplicates(games: u64, equivalence: Equivalence) {
+    println!("DUPLICATES NOT SAVED: {} (by {})", games, equivalence);
+}
+
+pub fn penalty_start(card: Card, required: u8) {
+    println!("\nNEW PENALTY PHASE: [{} - {}]\n", card.rank, required);
+}
+
+pub fn card_played(player: Player, card: &Card) {
+    println!("\nPLAYER |{}| →  {}", player.number(), card);
+}
+
+pub fn slapped(player: Player, slap: Slap) {
+    println!("\nPLAYER |{}| SLAPS THE PILE: {}", player.number(), slap);
+}
+
+pub fn false_slap(player: Player, burned: &[Card]) {
+    println!(
+        "\nPLAYER |{}| SLAPS IN ERROR AND BURNS [{}]",
+        player.number(),
+        print_cards(burned, ", ")
+    );
+}
+
+pub fn cards_collected(player: Player, cards: &[Card]) {
+    println!(
+        "\nPLAYER |{}| ←  [{}]",
+        player.number(),
+        print_cards(cards, ", ")
+    );
+    println!("\nEND PENALTY PHASE\n");
+}
+
+pub fn deck(deck: &DeckSpec) {
+    println!("DECK: {}", deck);
+}
+
+pub fn dealing(method: DealMethod) {
+    println!("DEAL METHOD: {}", method);
+}
+
+pub fn first(player: Player) {
+    println!("FIRST TO PLAY: PLAYER |{}|", player.number());
+}
+
+pub fn rules(rules: &Rules) {
+    println!("RULES: {}", rules);
+}
+
+pub fn agents(agents: &[Agent]) {
+    for (player, agent) in Player::all(agents.len()).zip(agents) {
+        println!(
+            "PLAYER |{}| SLAPS IN {}, SPOTTING {:.0}% AND MISTAKING {:.0}%",
+            player.number(),
+            agent.reaction,
+            agent.accuracy * 100.0,
+            agent.false_slaps * 100.0
+        );
+    }
+}
+
+pub fn player_eliminated(player: Player) {
+    println!("\nPLAYER |{}| IS OUT OF CARDS", player.number());
+}
+
+pub fn game_start() {
+    println!("\n=== Game Start ===");
+}
+
+pub fn game_over(outcome: GameOutcome) {
+    println!("\n=== Game Over ===");
+    match outcome {
+        GameOutcome::Winner(winner) => println!("WINNER: PLAYER {}", winner.number()),
+        GameOutcome::Infinite {
+            cycle_start,
+            cycle_length,
+        } => println!(
+            "INFINITE GAME: position after trick {} repeats every {} tricks",
+            cycle_start, cycle_length
+        ),
+    }
+}
+
+pub fn game_length(result: &GameResult) {
+    println!("\nCARDS PLAYED: {}", result.cards_played);
+    println!("TRICKS: {}", result.tricks);
+    println!("PENALTY PHASES: {}", result.penalty_phases);
+}
+
+pub fn full_starting_deck(deck: &[Card]) {
+    println!("\nINITIAL DECK ({}):", deck.len());
+    println!("[{}]", print_cards(deck, ", "));
+}
+
+pub fn player_starting_deck(player: Player, deck: &[Card]) {
+    println!(
+        "\nPLAYER |{}| INITIAL DECK ({}):",
+        player.number(),
+        deck.len()
+    );
+    println!("[{}]", print_cards(deck, ", "));
+}
+
+pub fn batch_report(stats: &Stats, elapsed: Duration) {
+    let seconds = elapsed.as_secs_f64();
+    let rate = if seconds > 0.0 {
+        stats.games() as f64 / seconds
+    } else {
+        0.0
+    };
+
+    println!("\n=== {} GAMES ({:.0} games/s) ===", stats.games(), rate);
+    for player in Player::all(stats.players()) {
+        println!(
+            "PLAYER |{}| WINS: {} ({:.2}%)",
+            player.number(),
+            stats.wins(player),
+            stats.win_rate(player) * 100.0
+        );
+    }
+    println!("INFINITE: {}", stats.infinite());
+    println!(
+        "LEADER WINS: {} ({:.2}%)",
+        stats.leader_wins(),
+        stats.leader_win_rate() * 100.0
+    );
+    for (name, lengths) in [("TRICKS", stats.tricks()), ("CARDS", stats.cards_played())] {
+        println!(
+            "{}: mean {:.1} | median {} | max {}",
+            name,
+            lengths.mean(),
+            lengths.median(),
+            lengths.max()
+        );
+    }
+
+    histogram(stats.cards_played());
+}
+
+/// Who won one deal of a batch under each leader, the first led by player
+/// one, named by its seed or, for swept deals, its index.
+pub fn leaders(seed: Option<u64>, index: Option<&DealIndex>, results: &[GameResult]) {
+    let deal = match (seed, index) {
+        (_, Some(index)) => format!("INDEX {}", index),
+        (Some(seed), None) => format!("SEED {}", seed),
+        (None, None) => "DEAL".to_string(),
+    };
+    let winners = results
+        .iter()
+        .enumerate()
+        .map(|(i, result)| {
+            let winner = match result.outcome {
+                GameOutcome::Winner(winner) => format!("PLAYER {}", winner.number()),
+                GameOutcome::Infinite { .. } => "INFINITE".to_string(),
+            };
+            format!("LEADER {} → {}", i + 1, winner)
+        })
+        .collect::<Vec<_>>();
+    println!("{}: {}", deal, winners.join(" | "));
+}
+
+/// How the deals of a batch played with every player leading turned out.
+pub fn orientations(orientations: &Orientations) {
+    let share = |deals: u64| {
+        if orientations.deals() == 0 {
+            return 0.0;
+        }
+        deals as f64 / orientations.deals() as f64 * 100.0
+    };
+
+    println!("\n=== {} DEALS, ONCE PER LEADER ===", orientations.deals());
+    for (name, deals) in [
+        ("SAME WINNER", orientations.same_winner()),
+        ("LEADER WON", orientations.leader_won()),
+        ("MIXED", orientations.mixed()),
+    ] {
+        println!("{}: {} ({:.2}%)", name, deals, share(deals));
+    }
+}
+
+fn histogram(lengths: &Distribution) {
+    const HISTOGRAM_BUCKETS: usize = 10;
+    const BAR_WIDTH: u64 = 40;
+
+    let width = (lengths.max() / HISTOGRAM_BUCKETS).max(1) + 1;
+    let histogram = lengths.histogram(width);
+    let tallest = histogram.iter().map(|&(_, games)| games).max().unwrap_or(0);
+
+    for (start, games) in histogram {
+        let bar = if tallest > 0 {
+            (games * BAR_WIDTH).div_ceil(tallest)
+        } else {
+            0
+        };
+        println!(
+            "{:>6}-{:<6} {:<width$} {}",
+            start,
+            start + width - 1,
+            "#".repeat(bar as usize),
+            games,
+            width = BAR_WIDTH as usize
+        );
+    }
+}
+
+pub fn deal(deal: &Deal) {
+    println!("\nDEAL: {}", deal);
+}
+
+pub fn experiment_start(config: &ExperimentConfig) {
+    println!(
+        "\n=== Experiment: {} games on {} threads ===",
+        config.games, config.threads
+    );
+}
+
+pub fn experiment_report(summary: &Summary, elapsed: Duration) {
+    batch_report(&summary.stats, elapsed);
+
+    let (first_player_wins, low, high) = summary.first_player_wins();
+    println!(
+        "\nFIRST PLAYER WINS: {:.2}% (95% CI {:.2}% to {:.2}%)",
+        first_player_wins * 100.0,
+        low * 100.0,
+        high * 100.0
+    );
+    println!("\nBY ACES IN THE FIRST PLAYER'S HAND:");
+    println!(
+        "{:>4} {:>10} {:>10} {:>10}",
+        "ACES", "GAMES", "WINS", "CARDS"
+    );
+    for (aces, stats) in summary.by_aces.iter().enumerate() {
+        if stats.games() == 0 {
+            continue;
+        }
+        println!(
+            "{:>4} {:>10} {:>9.2}% {:>10.1}",
+            aces,
+            stats.games(),
+            stats.leader_win_rate() * 100.0,
+            stats.cards_played().mean()
+        );
+    }
+}
+
+pub fn bench_start(config: &BenchConfig) {
+    println!("\n=== Bench: {} deals on one thread ===", config.games);
+}
+
+pub fn bench_report(report: &BenchReport) {
+    for (name, timing) in [
+        ("RANK ENGINE", report.engine),
+        ("REFERENCE", report.reference),
+    ] {
+        println!(
+            "{:<12} {} cards in {:.2}s | {:.0} games/s | {:.1}M cards/s",
+            format!("{}:", name),
+            timing.cards,
+            timing.elapsed.as_secs_f64(),
+            timing.games_per_second(),
+            timing.cards_per_second() / 1e6
+        );
+    }
+    println!("SPEEDUP: {:.1}x", report.speedup());
+    match report.mismatches.len() {
+        0 => println!("RESULTS: identical on all {} deals", report.engine.games),
+        n => println!("RESULTS: {} of {} deals differ", n, report.engine.games),
+    }
+}
+
+pub fn search_start(config: &SearchConfig) {
+    println!(
+        "\n=== Search: longest games by {} on {} threads ===",
+        config.metric, config.threads
+    );
+}
+
+pub fn search_record(entry: &Entry) {
+    println!("\n{}", format_entry(entry));
+}
+
+pub fn leaderboard(leaderboard: &Leaderboard) {
+    let boards = [
+        ("LONGEST BY TRICKS", leaderboard.by_tricks()),
+        ("LONGEST BY CARDS", leaderboard.by_cards()),
+        ("INFINITE", leaderboard.infinite()),
+    ];
+
+    for (title, entries) in boards {
+        println!("\n=== {} ===", title);
+        for (place, entry) in entries.iter().enumerate() {
+            println!("{:>3}. {}", place + 1, format_entry(entry));
+        }
+    }
+}
+
+fn format_entry(entry: &Entry) -> String {
+    let length = match entry.result.outcome {
+        GameOutcome::Winner(_) => format!(
+            "{} tricks, {} cards",
+            entry.result.tricks, entry.result.cards_played
+        ),
+        GameOutcome::Infinite {
+            cycle_start,
+            cycle_length,
+        } => format!(
+            "INFINITE from trick {} every {} tricks",
+            cycle_start, cycle_length
+        ),
+    };
+    format!("{} | {}", length, entry.deal)
+}
@@ -1,326 +1,546 @@
 // CRATES
 
-use rand::prelude::*;
-use rand::rngs::StdRng;
+mod cli;
+mod tui;
+mod viewer;
+
+use beggar_my_neighbour::bench::{self, BenchConfig};
+use beggar_my_neighbour::events::{EventSink, JsonLinesSink, PrettySink};
+use beggar_my_neighbour::experiment::{self, ExperimentConfig};
+use beggar_my_neighbour::index::DealSpace;
+use beggar_my_neighbour::ratscrew::{self, Slappers};
+use beggar_my_neighbour::search::{self, Metric, SearchConfig};
+use beggar_my_neighbour::store::{Record, Store};
+use beggar_my_neighbour::transcript::{Format, Transcript};
+use beggar_my_neighbour::{
+    logging, seed, Card, Deal, Game, GameResult, Orientations, Player, Stats,
+};
+use cli::{Args, Command, Events, StartingDeal};
 use std::collections::VecDeque;
-use std::fmt;
-
-// STRUCTS + METHODS
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Rank {
-    Two,
-    Three,
-    Four,
-    Five,
-    Six,
-    Seven,
-    Eight,
-    Nine,
-    Ten,
-    Jack,
-    Queen,
-    King,
-    Ace,
-}
+use std::io::{self, BufWriter};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+// CONSTANTS
+
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
 
-impl Rank {
-    fn penalty_value(&self) -> Option<u8> {
-        match self {
-            Rank::Jack => Some(1),
-            Rank::Queen => Some(2),
-            Rank::King => Some(3),
-            Rank::Ace => Some(4),
-            _ => None,
+// MAIN
+
+fn main() {
+    let args = match cli::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, cli::USAGE);
+            process::exit(2);
         }
-    }
-}
+    };
 
-impl fmt::Display for Rank {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Rank::Two => "2",
-                Rank::Three => "3",
-                Rank::Four => "4",
-                Rank::Five => "5",
-                Rank::Six => "6",
-                Rank::Seven => "7",
-                Rank::Eight => "8",
-                Rank::Nine => "9",
-                Rank::Ten => "10",
-                Rank::Jack => "J",
-                Rank::Queen => "Q",
-                Rank::King => "K",
-                Rank::Ace => "A",
-            }
-        )
+    if args.help {
+        println!("{}", cli::USAGE);
+        return;
     }
-}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Suit {
-    Spade,
-    Heart,
-    Club,
-    Diamond,
-}
+    let outcome = match args.command {
+        Command::Run if args.is_batch() => run_batch(&args),
+        Command::Run => run_single(&args),
+        Command::Replay(key) => replay(&args, key),
+        Command::Play => play_interactive(&args),
+        Command::View(key) => view(&args, key),
+        Command::Search => run_search(&args),
+        Command::Experiment => run_experiment(&args),
+        Command::Bench => run_bench(&args),
+    };
 
-impl fmt::Display for Suit {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Suit::Spade => "♤",
-                Suit::Heart => "♡",
-                Suit::Club => "♧",
-                Suit::Diamond => "♢",
-            }
-        )
+    if let Err(err) = outcome {
+        eprintln!("error: {}", err);
+        process::exit(1);
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Card {
-    rank: Rank,
-    suit: Suit,
-}
+// COMMANDS
 
-impl fmt::Display for Card {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}{}", self.rank, self.suit)
-    }
-}
+fn run_single(args: &Args) -> Result<(), String> {
+    let mut store = open_store(args)?;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum Player {
-    One,
-    Two,
-}
+    let (seed, starting_deck, decks) = starting_position(args);
+    let dealing = seed.map(|_| args.dealing());
+    if let (Some(seed), Some(dealing), Events::Pretty) = (seed, dealing, args.events) {
+        logging::seed(seed, args.shuffle);
+        logging::deck(&args.deck);
+        logging::dealing(dealing);
+    }
 
-impl Player {
-    fn other(&self) -> Self {
-        match self {
-            Player::One => Player::Two,
-            Player::Two => Player::One,
-        }
+    if args.events == Events::Pretty {
+        let deal = Deal::from_cards(&decks, &args.rules);
+        let space = DealSpace::of_deal(&deal);
+        let index = space.rank(&deal).expect("a deal is in its own space");
+        logging::deal_index(&index, &space.size());
     }
 
-    fn number(&self) -> usize {
-        match self {
-            Player::One => 1,
-            Player::Two => 2,
+    let first = first_player(args, seed, decks.len());
+    let agents = args.agents(decks.len())?;
+    let result = play(
+        Transcript {
+            shuffle: args.shuffle,
+            dealing,
+            first,
+            agents: agents.clone(),
+            ..Transcript::new(seed, &starting_deck, &decks, args.rules)
+        },
+        args,
+    )?;
+
+    if let Some(store) = &mut store {
+        let record = Record {
+            agents,
+            shuffle: args.shuffle,
+            dealing,
+            deal_index: args.deal_index.clone(),
+            first,
+            ..Record::new(seed, &starting_deck, &decks, args.rules, &result)
+        };
+        match store.duplicate(&record) {
+            Some(id) if args.events == Events::Pretty => logging::duplicate(id, store.path()),
+            Some(_) => {}
+            None => {
+                let record = save(store, record)?;
+                if args.events == Events::Pretty {
+                    logging::saved(record.id, store.path());
+                }
+            }
         }
     }
+
+    Ok(())
 }
 
-// LOGGING MODULE
+fn run_batch(args: &Args) -> Result<(), String> {
+    let mut store = open_store(args)?;
+    let mut stats = Stats::new(args.players);
+    let mut orientations = Orientations::default();
+    let mut deals = 0;
+    let mut duplicates = 0;
+
+    let started = Instant::now();
+    let mut last_report = started;
+    let mut next_seed = args.seed;
+    let agents = args.agents(args.players)?;
+    let dealing = args.dealing();
+    let cards = args.deck.cards();
+    let space = args.deal_space();
+    let mut sweep = match &args.deal_index {
+        Some(start) => Some(space.deals_from(start)?),
+        None => None,
+    };
 
-mod logging {
-    use super::{Card, Player};
+    logging::deck(&args.deck);
+    logging::dealing(dealing);
+    logging::rules(&args.rules);
+    logging::agents(&agents);
+
+    while args.games.is_none_or(|games| deals < games) {
+        // A swept deal has no seed of its own, so a random leader is drawn
+        // from its index instead. Its slappers react the way they do in any
+        // fixed deal, which is how the saved record replays.
+        let (seed, pick_seed, deal_index, starting_deck, decks) = match &mut sweep {
+            Some(sweep) => {
+                let Some((index, deal)) = sweep.next() else {
+                    break;
+                };
+                let decks = deal
+                    .to_cards(&cards, &args.rules)
+                    .expect("an indexed deal holds the deck");
+                let starting_deck = decks.iter().flatten().copied().collect::<Vec<_>>();
+                let pick_seed = seed::hash_seed_text(&index.to_string());
+                (None, pick_seed, Some(index), starting_deck, decks)
+            }
+            None => {
+                let seed = match next_seed {
+                    Some(seed) => {
+                        next_seed = Some(seed.wrapping_add(1));
+                        seed
+                    }
+                    None => seed::random_seed(),
+                };
+                let starting_deck = args.shuffle.shuffle(seed, cards.clone());
+                let decks = dealing.deal(&starting_deck, args.players);
+                (Some(seed), seed, None, starting_deck, decks)
+            }
+        };
 
-    pub fn penalty_start(required: u8) {
-        let rune: &str = match required {
-            1 => "J",
-            2 => "Q",
-            3 => "K",
-            _ => "A",
+        let leaders = if args.each_first {
+            Player::all(args.players).collect()
+        } else {
+            vec![args
+                .first
+                .pick(deals, pick_seed, args.shuffle, args.players)]
         };
-        println!("\nNEW PENALTY PHASE: [{} - {}]\n", rune, required);
-    }
 
-    pub fn card_played(player: Player, card: &Card) {
-        println!("\nPLAYER |{}| →  {}", player.number(), card);
-    }
+        let mut results = Vec::with_capacity(leaders.len());
+        for first in leaders {
+            let mut game = Game::new(decks.clone())
+                .with_rules(args.rules)
+                .with_first(first);
+            let mut slappers = Slappers::new(&agents, seed.unwrap_or_default(), args.shuffle);
+            let result = finish(&mut game, &mut slappers);
+            stats.record(&result, first);
+
+            if let Some(store) = &mut store {
+                let record = Record {
+                    agents: agents.clone(),
+                    shuffle: args.shuffle,
+                    dealing: Some(dealing),
+                    deal_index: deal_index.clone(),
+                    first,
+                    ..Record::new(seed, &starting_deck, &decks, args.rules, &result)
+                };
+                match store.duplicate(&record) {
+                    Some(_) => duplicates += 1,
+                    None => {
+                        save(store, record)?;
+                    }
+                }
+            }
+            results.push(result);
+        }
+        if args.each_first {
+            logging::leaders(seed, deal_index.as_ref(), &results);
+            orientations.record(&results);
+        }
+        deals += 1;
 
-    pub fn cards_collected(player: Player, cards: &[Card]) {
-        let cards_str = cards
-            .iter()
-            .map(|c| c.to_string())
-            .collect::<Vec<_>>()
-            .join(", ");
-        println!("\nPLAYER |{}| ←  [{}]", player.number(), cards_str);
-        println!("\nEND PENALTY PHASE\n");
+        if last_report.elapsed() >= REPORT_INTERVAL {
+            logging::batch_report(&stats, started.elapsed());
+            last_report = Instant::now();
+        }
     }
 
-    pub fn game_start() {
-        println!("\n=== Game Start ===");
+    logging::batch_report(&stats, started.elapsed());
+    if let Some(equivalence) = args.dedup {
+        logging::duplicates(duplicates, equivalence);
     }
-
-    pub fn game_over(winner: Player) {
-        println!("\n=== Game Over ===");
-        println!("WINNER: PLAYER {}", winner.number());
+    if args.each_first {
+        logging::orientations(&orientations);
     }
+    Ok(())
+}
 
-    pub fn turn_count(count: usize) {
-        println!("\nTURNS: {}", count);
+fn replay(args: &Args, key: u64) -> Result<(), String> {
+    let record = find_record(args, key)?;
+    if args.events == Events::Pretty {
+        logging::replay(record.id);
+        if let Some(seed) = record.seed {
+            logging::seed(seed, record.shuffle);
+        }
+        if let Some(index) = &record.deal_index {
+            let decks = record
+                .initial_decks
+                .iter()
+                .map(|deck| deck.iter().copied().collect())
+                .collect::<Vec<_>>();
+            let deal = Deal::from_cards(&decks, &record.rules);
+            logging::deal_index(index, &DealSpace::of_deal(&deal).size());
+        }
+        if let Some(dealing) = record.dealing {
+            logging::dealing(dealing);
+        }
     }
 
-    fn print_deck(deck: &[Card]) -> String {
-        deck.iter()
-            .map(|c| c.to_string())
-            .collect::<Vec<_>>()
-            .join(", ")
-    }
+    let result = play(Transcript::from(&record), args)?;
 
-    pub fn full_starting_deck(deck: &[Card]) {
-        println!("\nINITIAL DECK ({}):", deck.len());
-        println!("[{}]", print_deck(deck));
+    if !record.matches(&result) {
+        return Err(format!(
+            "replay of #{} does not match the saved result",
+            record.id
+        ));
     }
 
-    pub fn player_starting_deck(player: Player, deck: &[Card]) {
-        println!(
-            "\nPLAYER |{}| INITIAL DECK ({}):",
-            player.number(),
-            deck.len()
-        );
-        println!("[{}]", print_deck(deck));
-    }
+    Ok(())
 }
 
-// HELPER FUNCTIONS
+fn play_interactive(args: &Args) -> Result<(), String> {
+    let (seed, _, decks) = starting_position(args);
+    let agents = args.agents(decks.len())?;
 
-fn create_deck() -> Vec<Card> {
-    let ranks = [
-        Rank::Two,
-        Rank::Three,
-        Rank::Four,
-        Rank::Five,
-        Rank::Six,
-        Rank::Seven,
-        Rank::Eight,
-        Rank::Nine,
-        Rank::Ten,
-        Rank::Jack,
-        Rank::Queen,
-        Rank::King,
-        Rank::Ace,
-    ];
-    let suits = [Suit::Spade, Suit::Heart, Suit::Club, Suit::Diamond];
-
-    let mut deck = Vec::with_capacity(52);
-    for &suit in &suits {
-        for &rank in &ranks {
-            deck.push(Card { rank, suit });
-        }
-    }
-    deck
-}
+    let human = Player::ONE;
+    let first = first_player(args, seed, decks.len());
+    let game = Game::new(decks)
+        .with_rules(args.rules)
+        .with_first(first)
+        .with_sink(tui::Commentary::new(human));
+    let slappers = Slappers::new(&agents, seed.unwrap_or_default(), args.shuffle).without(human);
 
-fn process_penalty_phase(
-    initial_player: Player,
-    initial_penalty: u8,
-    central_pile: &mut Vec<Card>,
-    decks: &mut [VecDeque<Card>; 2],
-) -> Option<Player> {
-    let mut current_player = initial_player.other();
-    let mut required = initial_penalty;
-    let mut last_penalty_initiator = initial_player;
+    let result =
+        tui::run(game, slappers, human).map_err(|err| format!("terminal error: {}", err))?;
 
-    logging::penalty_start(required);
+    if let Some(seed) = seed {
+        logging::seed(seed, args.shuffle);
+    }
+    if let Some(result) = result {
+        logging::game_over(result.outcome);
+        logging::game_length(&result);
+    }
+    Ok(())
+}
 
-    loop {
-        let mut paid = 0;
+fn view(args: &Args, key: Option<u64>) -> Result<(), String> {
+    let (decks, rules, first, slappers) = match key {
+        Some(key) => {
+            let record = find_record(args, key)?;
+            let seed = record.seed.unwrap_or_default();
+            let slappers = Slappers::new(&record.agents, seed, record.shuffle);
+            (record.hands(), record.rules, record.first, slappers)
+        }
+        None => {
+            let (seed, _, decks) = starting_position(args);
+            let first = first_player(args, seed, decks.len());
+            let agents = args.agents(decks.len())?;
+            let slappers = Slappers::new(&agents, seed.unwrap_or_default(), args.shuffle);
+            (decks, args.rules, first, slappers)
+        }
+    };
 
-        while paid < required {
-            let player_idx = current_player as usize;
+    let game = Game::new(decks)
+        .with_rules(rules)
+        .with_first(first)
+        .with_sink(viewer::Notes::default());
+    let timeline = viewer::Timeline::record(game, slappers);
+    viewer::run(&timeline).map_err(|err| format!("terminal error: {}", err))
+}
 
-            if decks[player_idx].is_empty() {
-                return Some(current_player.other());
+fn run_search(args: &Args) -> Result<(), String> {
+    let config = SearchConfig {
+        rules: args.rules,
+        deck: args.deck.clone(),
+        threads: args.threads,
+        seed: args.seed.unwrap_or_else(seed::random_seed),
+        ..args.search.clone()
+    };
+    logging::seed(config.seed, args.shuffle);
+    logging::deck(&config.deck);
+    logging::rules(&config.rules);
+    logging::search_start(&config);
+
+    let best = AtomicUsize::new(0);
+    let infinite = AtomicUsize::new(0);
+
+    let leaderboard = search::search(&config, |board| {
+        let leaders = match config.metric {
+            Metric::Tricks => board.by_tricks(),
+            Metric::Cards => board.by_cards(),
+        };
+        if let Some(leader) = leaders.first() {
+            let score = config.metric.score(&leader.result);
+            if best.fetch_max(score, Ordering::Relaxed) < score {
+                logging::search_record(leader);
             }
-
-            let card = decks[player_idx].pop_front().unwrap();
-            central_pile.push(card);
-            paid += 1;
-
-            logging::card_played(current_player, &card);
-
-            if let Some(new_penalty) = card.rank.penalty_value() {
-                logging::penalty_start(new_penalty);
-                last_penalty_initiator = current_player;
-                required = new_penalty;
-                current_player = current_player.other();
-                paid = 0;
-                break;
+        }
+        if let Some(found) = board.infinite().last() {
+            if infinite.fetch_max(board.infinite().len(), Ordering::Relaxed)
+                < board.infinite().len()
+            {
+                logging::search_record(found);
             }
         }
+    });
 
-        if paid == required {
-            logging::cards_collected(last_penalty_initiator, central_pile);
+    logging::leaderboard(&leaderboard);
+    Ok(())
+}
+
+fn run_experiment(args: &Args) -> Result<(), String> {
+    let config = ExperimentConfig {
+        rules: args.rules,
+        deck: args.deck.clone(),
+        players: args.players,
+        dealing: args.dealing(),
+        first: args.first,
+        agents: args.agents(args.players)?,
+        shuffle: args.shuffle,
+        threads: args.threads,
+        games: args.games.unwrap_or(ExperimentConfig::default().games),
+        seed: args.seed.unwrap_or_else(seed::random_seed),
+    };
 
-            let target_idx = last_penalty_initiator as usize;
-            decks[target_idx].extend(central_pile.drain(..));
-            return None;
+    let started = Instant::now();
+    if args.output.is_none() {
+        logging::seed(config.seed, config.shuffle);
+        logging::deck(&config.deck);
+        logging::dealing(config.dealing);
+        logging::rules(&config.rules);
+        logging::agents(&config.agents);
+        logging::experiment_start(&config);
+    }
+    let summary = experiment::run(&config);
+
+    let out = BufWriter::new(io::stdout());
+    let written = match args.output {
+        Some(Format::Json) => summary.write_json(&config, out),
+        Some(Format::Csv) => summary.write_csv(&config, out),
+        Some(Format::Text) | None => {
+            logging::experiment_report(&summary, started.elapsed());
+            Ok(())
         }
+    };
+    written.map_err(|err| format!("could not write summary: {}", err))
+}
+
+fn run_bench(args: &Args) -> Result<(), String> {
+    let config = BenchConfig {
+        rules: args.rules,
+        deck: args.deck.clone(),
+        players: args.players,
+        dealing: args.dealing(),
+        first: args.first,
+        shuffle: args.shuffle,
+        games: args.games.unwrap_or(BenchConfig::default().games),
+        seed: args.seed.unwrap_or_else(seed::random_seed),
+    };
+    logging::seed(config.seed, config.shuffle);
+    logging::deck(&config.deck);
+    logging::dealing(config.dealing);
+    logging::rules(&config.rules);
+    logging::bench_start(&config);
+
+    let report = bench::run(&config);
+    logging::bench_report(&report);
+
+    match report.mismatches.first() {
+        Some(seed) => Err(format!(
+            "the engines disagree on {} deals, the first dealt from seed {}",
+            report.mismatches.len(),
+            seed
+        )),
+        None => Ok(()),
     }
 }
 
-// MAIN GAME LOOP
+// HELPER FUNCTIONS
 
-fn main() {
-    let seed = rand::rng().next_u64();
-    println!("SEED: {}", seed);
-
-    let mut rng = StdRng::seed_from_u64(seed);
-    let mut deck = create_deck();
-    deck.shuffle(&mut rng);
-
-    let starting_deck = deck.iter().copied().collect::<Vec<_>>();
-    let split_point = deck.len() / 2;
-    let mut decks = [
-        deck.drain(..split_point).collect::<VecDeque<_>>(),
-        deck.drain(..).collect::<VecDeque<_>>(),
-    ];
-
-    let initial_decks = [
-        decks[0].iter().copied().collect::<Vec<_>>(),
-        decks[1].iter().copied().collect::<Vec<_>>(),
-    ];
-
-    let mut turn_count = 0;
-    let mut current_player = Player::One;
-    let mut central_pile = Vec::new();
-
-    logging::full_starting_deck(&starting_deck);
-    logging::player_starting_deck(Player::One, &initial_decks[0]);
-    logging::player_starting_deck(Player::Two, &initial_decks[1]);
-
-    logging::game_start();
-    let winner = loop {
-        let player_idx = current_player as usize;
-
-        if decks[player_idx].is_empty() {
-            break current_player.other();
+/// The seed, starting deck and hands for a single game: the `--deal` if one
+/// was given, otherwise a deck shuffled from the seed.
+fn starting_position(args: &Args) -> (Option<u64>, Vec<Card>, Vec<VecDeque<Card>>) {
+    match &args.deal {
+        Some(deal) => {
+            let decks = match deal {
+                StartingDeal::Ranks(deal) => deal
+                    .to_cards(&args.deck.cards(), &args.rules)
+                    .expect("the deal was checked against the deck"),
+                StartingDeal::Cards(hands) => hands.to_decks(),
+            };
+            let starting_deck = decks.iter().flatten().copied().collect::<Vec<_>>();
+            (None, starting_deck, decks)
         }
+        None => {
+            let seed = args.seed.unwrap_or_else(seed::random_seed);
+            let starting_deck = args.shuffle.shuffle(seed, args.deck.cards());
+            let decks = args.dealing().deal(&starting_deck, args.players);
+            (Some(seed), starting_deck, decks)
+        }
+    }
+}
 
-        let card = decks[player_idx].pop_front().unwrap();
-        central_pile.push(card);
+/// Who leads a single game at a table of `players`. A random pick comes from
+/// the game's seed, or a fresh one for a fixed deal.
+fn first_player(args: &Args, seed: Option<u64>, players: usize) -> Player {
+    let seed = seed.unwrap_or_else(seed::random_seed);
+    args.first.pick(0, seed, args.shuffle, players)
+}
 
-        logging::card_played(current_player, &card);
+/// Play the game `setup` describes, a transcript with no moves yet,
+/// reporting it the way `args` ask.
+fn play(setup: Transcript, args: &Args) -> Result<GameResult, String> {
+    let decks = setup
+        .initial_decks
+        .iter()
+        .map(|deck| deck.iter().copied().collect())
+        .collect::<Vec<VecDeque<_>>>();
+    let rules = setup.rules;
+    let mut game = Game::new(decks.clone())
+        .with_rules(rules)
+        .with_first(setup.first);
+    let mut slappers = Slappers::new(&setup.agents, setup.seed.unwrap_or_default(), setup.shuffle);
+
+    if let Some(format) = args.output {
+        let mut game = game.with_sink(setup);
+        let result = finish(&mut game, &mut slappers);
+
+        game.into_sink()
+            .write(format, BufWriter::new(io::stdout()))
+            .map_err(|err| format!("could not write transcript: {}", err))?;
+        return Ok(result);
+    }
 
-        if let Some(penalty) = card.rank.penalty_value() {
-            if let Some(winner) =
-                process_penalty_phase(current_player, penalty, &mut central_pile, &mut decks)
-            {
-                break winner;
+    match args.events {
+        Events::Pretty => {
+            logging::full_starting_deck(&setup.starting_deck);
+            for (player, deck) in Player::all(decks.len()).zip(&setup.initial_decks) {
+                logging::player_starting_deck(player, deck);
             }
-        } else {
-            current_player = current_player.other();
-            turn_count += 1;
-        }
+            logging::deal(&Deal::from_cards(&decks, &rules));
+            logging::rules(&rules);
+            logging::first(setup.first);
+            logging::agents(&setup.agents);
+            logging::game_start();
 
-        if decks[0].is_empty() || decks[1].is_empty() {
-            break if decks[0].is_empty() {
-                Player::Two
-            } else {
-                Player::One
-            };
+            Ok(finish(&mut game.with_sink(PrettySink), &mut slappers))
         }
-    };
+        Events::Json => {
+            let mut game = game.with_sink(JsonLinesSink::new(BufWriter::new(io::stdout())));
+            let result = finish(&mut game, &mut slappers);
+
+            game.into_sink()
+                .into_inner()
+                .map_err(|err| format!("could not write events: {}", err))?;
+            Ok(result)
+        }
+        Events::None => Ok(finish(&mut game, &mut slappers)),
+    }
+}
+
+/// Play `game` to the end, with `slappers` slapping the pile if the rules
+/// allow slaps.
+fn finish<S: EventSink>(game: &mut Game<S>, slappers: &mut Slappers) -> GameResult {
+    if game.rules().slaps.enabled() {
+        return ratscrew::play(game, slappers);
+    }
+    game.run()
+}
+
+fn find_record(args: &Args, key: u64) -> Result<Record, String> {
+    let store = Store::open(&args.db)
+        .map_err(|err| format!("could not open {}: {}", args.db.display(), err))?;
+
+    store
+        .find(key)
+        .map_err(|err| format!("could not read {}: {}", store.path().display(), err))?
+        .ok_or_else(|| {
+            format!(
+                "no record with id or seed {} in {}",
+                key,
+                store.path().display()
+            )
+        })
+}
+
+fn open_store(args: &Args) -> Result<Option<Store>, String> {
+    if !args.save {
+        return Ok(None);
+    }
+
+    let store = Store::open(&args.db).and_then(|store| match args.dedup {
+        Some(equivalence) => store.deduplicate(equivalence),
+        None => Ok(store),
+    });
+    store
+        .map(Some)
+        .map_err(|err| format!("could not open {}: {}", args.db.display(), err))
+}
 
-    logging::game_over(winner);
-    logging::turn_count(turn_count);
+fn save(store: &mut Store, record: Record) -> Result<Record, String> {
+    store
+        .append(record)
+        .map_err(|err| format!("could not save to {}: {}", store.path().display(), err))
 }
@@ -0,0 +1,152 @@
+// CRATES
+
+use crate::seed::{self, ShuffleVersion};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+// CONSTANTS
+
+pub const MIN_PLAYERS: usize = 2;
+pub const MAX_PLAYERS: usize = 8;
+
+/// Mixed into the game seed so picking the first player draws from a
+/// different stream than the shuffle.
+const SEED_SALT: u64 = 0xf125_7f12_57f1_257f;
+
+// STRUCTS + METHODS
+
+/// A seat at the table, counted clockwise from player one, who leads first
+/// unless told otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Player(u8);
+
+impl Player {
+    pub const ONE: Player = Player(0);
+    pub const TWO: Player = Player(1);
+
+    pub fn new(index: usize) -> Self {
+        assert!(index < MAX_PLAYERS, "no seat {} at the table", index);
+        Player(index as u8)
+    }
+
+    /// Every seat at a table of `players`, in turn order.
+    pub fn all(players: usize) -> impl Iterator<Item = Player> {
+        (0..players).map(Player::new)
+    }
+
+    /// The seat to this player's left at a table of `players`.
+    pub fn next(&self, players: usize) -> Self {
+        Player::new((self.index() + 1) % players)
+    }
+
+    pub fn number(&self) -> usize {
+        self.index() + 1
+    }
+
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Who leads the first trick of each game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum First {
+    /// The same player every game.
+    Player(Player),
+    /// A player drawn from the game's seed.
+    Random,
+    /// Each player in turn, game by game, starting with player one.
+    Alternate,
+}
+
+impl Default for First {
+    fn default() -> Self {
+        First::Player(Player::ONE)
+    }
+}
+
+impl First {
+    /// The player who leads game number `game` of a run, dealt from `seed`
+    /// at a table of `players`.
+    pub fn pick(&self, game: u64, seed: u64, shuffle: ShuffleVersion, players: usize) -> Player {
+        match *self {
+            First::Player(player) => player,
+            First::Random => {
+                let mut rng = shuffle.rng(seed ^ SEED_SALT);
+                Player::new(seed::below(&mut rng, players as u32) as usize)
+            }
+            First::Alternate => Player::new((game % players as u64) as usize),
+        }
+    }
+}
+
+impl fmt::Display for First {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            First::Player(player) => write!(f, "{}", player.number()),
+            First::Random => write!(f, "random"),
+            First::Alternate => write!(f, "alternate"),
+        }
+    }
+}
+
+impl FromStr for First {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(First::Random),
+            "alternate" => Ok(First::Alternate),
+            _ => match s.parse::<usize>() {
+                Ok(number) if (1..=MAX_PLAYERS).contains(&number) => {
+                    Ok(First::Player(Player::new(number - 1)))
+                }
+                _ => Err(format!(
+                    "unknown first player '{}', expected 1 to {}, random or alternate",
+                    s, MAX_PLAYERS
+                )),
+            },
+        }
+    }
+}
+
+impl Serialize for Player {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.number() as u64)
+    }
+}
+
+impl<'de> Deserialize<'de> for Player {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Two-player records saved before N-player support name the seat.
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Seat {
+            Number(usize),
+            Name(String),
+        }
+
+        let number = match Seat::deserialize(deserializer)? {
+            Seat::Number(number) => number,
+            Seat::Name(name) => match name.as_str() {
+                "One" => 1,
+                "Two" => 2,
+                _ => {
+                    return Err(serde::de::Error::custom(format!(
+                        "unknown player '{}'",
+                        name
+                    )))
+                }
+            },
+        };
+
+        if !(1..=MAX_PLAYERS).contains(&number) {
+            return Err(serde::de::Error::custom(format!(
+                "player {} is outside 1 to {}",
+                number, MAX_PLAYERS
+            )));
+        }
+        Ok(Player::new(number - 1))
+    }
+}
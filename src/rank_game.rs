@@ -0,0 +1,418 @@
+// CRATES
+
+use crate::player::MAX_PLAYERS;
+use crate::rules::{Leader, OutOfCards, Rules};
+use crate::{Deal, GameOutcome, GameResult, Player};
+
+// CONSTANTS
+
+const HAND_SEPARATOR: u8 = u8::MAX;
+/// Slots in the table of seen positions at the start of every game.
+const MIN_TABLE: usize = 64;
+/// The multiplier FxHash uses, which spreads a word well enough for a table
+/// indexed by its top bits.
+const HASH_MULTIPLIER: u64 = 0x517c_c1b7_2722_0a95;
+
+// STRUCTS + METHODS
+
+/// A hand of penalty values in a ring buffer with room for every card in the
+/// deal, so it never grows while a game is played.
+#[derive(Debug, Clone, Default)]
+struct Hand {
+    /// A power of two in length, so positions wrap with a mask.
+    cards: Box<[u8]>,
+    front: usize,
+    len: usize,
+}
+
+impl Hand {
+    /// Hold exactly `cards`, making room for `capacity` if there is not
+    /// enough already.
+    fn deal(&mut self, cards: &[u8], capacity: usize) {
+        if self.cards.len() < capacity {
+            self.cards = vec![0; capacity].into_boxed_slice();
+        }
+        self.cards[..cards.len()].copy_from_slice(cards);
+        self.front = 0;
+        self.len = cards.len();
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn pop_front(&mut self) -> Option<u8> {
+        if self.len == 0 {
+            return None;
+        }
+        let card = self.cards[self.front];
+        self.front = (self.front + 1) & (self.cards.len() - 1);
+        self.len -= 1;
+        Some(card)
+    }
+
+    fn extend(&mut self, cards: &[u8]) {
+        let mask = self.cards.len() - 1;
+        for &card in cards {
+            self.cards[(self.front + self.len) & mask] = card;
+            self.len += 1;
+        }
+    }
+
+    /// The cards, front first, in the one or two runs they sit in.
+    fn as_slices(&self) -> (&[u8], &[u8]) {
+        let end = self.front + self.len;
+        if end <= self.cards.len() {
+            (&self.cards[self.front..end], &[])
+        } else {
+            (
+                &self.cards[self.front..],
+                &self.cards[..end - self.cards.len()],
+            )
+        }
+    }
+}
+
+/// Every position seen after a trick so far this game, stored back to back
+/// and found again through an open-addressing hash table. The buffers are
+/// kept from game to game, so once they have grown nothing is allocated.
+#[derive(Debug, Clone, Default)]
+struct Positions {
+    /// The position after trick `i` at `keys[i * width..]`. The pile is
+    /// always empty then, so every position of a game has the same width.
+    keys: Vec<u8>,
+    hashes: Vec<u64>,
+    /// A position's trick plus one, or zero for an empty slot.
+    table: Vec<u32>,
+    width: usize,
+}
+
+impl Positions {
+    fn clear(&mut self, width: usize) {
+        self.keys.clear();
+        self.hashes.clear();
+        self.table.clear();
+        self.table.resize(MIN_TABLE, 0);
+        self.width = width;
+    }
+
+    /// Remember the position with `hands` as they are and `current_player`
+    /// to lead. If it has been seen before, returns the trick it was first
+    /// reached after instead.
+    fn visit(&mut self, hands: &[Hand], current_player: Player) -> Option<usize> {
+        let start = self.keys.len();
+        self.keys.push(current_player.index() as u8);
+        for hand in hands {
+            let (front, back) = hand.as_slices();
+            self.keys.extend_from_slice(front);
+            self.keys.extend_from_slice(back);
+            self.keys.push(HAND_SEPARATOR);
+        }
+
+        let hash = hash(&self.keys[start..]);
+        let mask = self.table.len() - 1;
+        let mut slot = self.slot(hash);
+        while let Some(seen) = self.table[slot].checked_sub(1).map(|seen| seen as usize) {
+            let key = &self.keys[seen * self.width..(seen + 1) * self.width];
+            if self.hashes[seen] == hash && *key == self.keys[start..] {
+                self.keys.truncate(start);
+                return Some(seen);
+            }
+            slot = (slot + 1) & mask;
+        }
+
+        self.table[slot] = self.hashes.len() as u32 + 1;
+        self.hashes.push(hash);
+        if self.hashes.len() * 2 > self.table.len() {
+            self.grow();
+        }
+        None
+    }
+
+    fn slot(&self, hash: u64) -> usize {
+        (hash >> (64 - self.table.len().trailing_zeros())) as usize
+    }
+
+    fn grow(&mut self) {
+        let size = self.table.len() * 2;
+        self.table.clear();
+        self.table.resize(size, 0);
+
+        for (seen, &hash) in self.hashes.iter().enumerate() {
+            let mut slot = self.slot(hash);
+            while self.table[slot] != 0 {
+                slot = (slot + 1) & (size - 1);
+            }
+            self.table[slot] = seen as u32 + 1;
+        }
+    }
+}
+
+/// A rank-only engine to play deal after deal with. Hands are ring buffers
+/// of penalty values and positions are kept in flat buffers, all reused from
+/// one game to the next, so a worker that keeps an engine allocates nothing
+/// per game once it has played its largest deal.
+#[derive(Debug, Clone, Default)]
+pub struct Engine {
+    hands: Vec<Hand>,
+    pile: Vec<u8>,
+    positions: Positions,
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Engine::default()
+    }
+
+    /// Play the deal with these hands of penalty values to the end, with
+    /// `first` leading the first trick. The result is the one
+    /// [`Game`](crate::Game) gives for the same deal.
+    pub fn play(&mut self, hands: &[&[u8]], rules: &Rules, first: Player) -> GameResult {
+        let players = hands.len();
+        let cards = hands.iter().map(|hand| hand.len()).sum::<usize>();
+        let capacity = cards.next_power_of_two();
+
+        self.hands.resize_with(players, Hand::default);
+        for (hand, cards) in self.hands.iter_mut().zip(hands) {
+            hand.deal(cards, capacity);
+        }
+        self.pile.clear();
+        self.pile.reserve(cards);
+        self.positions.clear(cards + players + 1);
+
+        let Engine {
+            hands,
+            pile,
+            positions,
+        } = self;
+        let mut live = [false; MAX_PLAYERS];
+        live[..players].fill(true);
+        let live = &mut live[..players];
+        let mut current_player = first;
+
+        let mut result = GameResult {
+            outcome: GameOutcome::Winner(Player::ONE),
+            cards_played: 0,
+            tricks: 0,
+            penalty_phases: 0,
+        };
+
+        let next_live = |live: &[bool], player: Player| {
+            let mut next = player.next(players);
+            while !live[next.index()] && next != player {
+                next = next.next(players);
+            }
+            next
+        };
+
+        positions.visit(hands, current_player);
+
+        let winner = 'game: loop {
+            let player = current_player;
+            if let Some(card) = hands[player.index()].pop_front() {
+                pile.push(card);
+                result.cards_played += 1;
+
+                if card == 0 {
+                    current_player = next_live(live, player);
+                } else {
+                    let mut last_penalty_initiator = player;
+                    let mut payer = next_live(live, player);
+                    let mut required = card;
+                    let mut paid = 0;
+                    result.penalty_phases += 1;
+
+                    while paid < required {
+                        if payer == last_penalty_initiator {
+                            break 'game last_penalty_initiator;
+                        }
+
+                        let Some(card) = hands[payer.index()].pop_front() else {
+                            live[payer.index()] = false;
+                            if rules.out_of_cards == OutOfCards::Forfeit {
+                                break;
+                            }
+                            payer = next_live(live, payer);
+                            continue;
+                        };
+                        pile.push(card);
+                        result.cards_played += 1;
+                        paid += 1;
+
+                        if card != 0 {
+                            result.penalty_phases += 1;
+                            last_penalty_initiator = payer;
+                            required = card;
+                            payer = next_live(live, payer);
+                            paid = 0;
+                        }
+                    }
+
+                    hands[last_penalty_initiator.index()].extend(pile);
+                    pile.clear();
+                    result.tricks += 1;
+                    if rules.leader == Leader::Collector {
+                        current_player = last_penalty_initiator;
+                    }
+
+                    if let Some(winner) = settle(hands, live, current_player) {
+                        break winner;
+                    }
+                    if !live[current_player.index()] {
+                        current_player = next_live(live, current_player);
+                    }
+
+                    if let Some(cycle_start) = positions.visit(hands, current_player) {
+                        result.outcome = GameOutcome::Infinite {
+                            cycle_start,
+                            cycle_length: result.tricks - cycle_start,
+                        };
+                        return result;
+                    }
+                    continue;
+                }
+            }
+
+            if let Some(winner) = settle(hands, live, current_player) {
+                break winner;
+            }
+            if !live[current_player.index()] {
+                current_player = next_live(live, current_player);
+            }
+        };
+
+        result.outcome = GameOutcome::Winner(winner);
+        result
+    }
+}
+
+// HELPER FUNCTIONS
+
+/// Play a rank-only deal to the end. This follows exactly the same rules as
+/// [`Game`](crate::Game) but moves bare penalty values instead of cards and
+/// never logs, so it is the path to use when playing very many deals; keep
+/// an [`Engine`] to play them without allocating.
+///
+/// Positions are compared by penalty value alone when looking for cycles, so
+/// an infinite game is caught the first time its ranks repeat. Slap rules
+/// need real ranks and are ignored.
+pub fn play(deal: &Deal, rules: &Rules) -> GameResult {
+    let hands = deal.hands().iter().map(Vec::as_slice).collect::<Vec<_>>();
+    play_hands(&hands, rules)
+}
+
+pub fn play_hands(hands: &[&[u8]], rules: &Rules) -> GameResult {
+    Engine::new().play(hands, rules, Player::ONE)
+}
+
+/// Knock out everyone left without cards, returning the winner if only one
+/// player remains, or `current_player` if nobody does.
+fn settle(hands: &[Hand], live: &mut [bool], current_player: Player) -> Option<Player> {
+    for (hand, live) in hands.iter().zip(live.iter_mut()) {
+        if hand.is_empty() {
+            *live = false;
+        }
+    }
+
+    let mut remaining = (0..live.len()).filter(|&i| live[i]);
+    match (remaining.next(), remaining.next()) {
+        (Some(winner), None) => Some(Player::new(winner)),
+        (None, _) => Some(current_player),
+        _ => None,
+    }
+}
+
+fn hash(key: &[u8]) -> u64 {
+    let mut words = key.chunks_exact(8);
+    let mut hash = 0u64;
+    for word in &mut words {
+        let word = u64::from_le_bytes(word.try_into().unwrap());
+        hash = (hash.rotate_left(5) ^ word).wrapping_mul(HASH_MULTIPLIER);
+    }
+    for &byte in words.remainder() {
+        hash = (hash.rotate_left(5) ^ byte as u64).wrapping_mul(HASH_MULTIPLIER);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::Pack;
+    use crate::seed::ShuffleVersion;
+    use crate::{DealMethod, DeckSpec, Game};
+
+    /// Play `games` seeded deals through both engines, checking they agree.
+    fn agrees_with_game(deck: DeckSpec, players: usize, rules: Rules, games: u64) {
+        let mut engine = Engine::new();
+        for seed in 0..games {
+            let cards = ShuffleVersion::default().shuffle(seed, deck.cards());
+            let hands = DealMethod::classic(players).deal(&cards, players);
+            let first = Player::new(seed as usize % players);
+
+            let deal = Deal::from_cards(&hands, &rules);
+            let penalties = deal.hands().iter().map(Vec::as_slice).collect::<Vec<_>>();
+            let fast = engine.play(&penalties, &rules, first);
+            let reference = Game::new(hands).with_rules(rules).with_first(first).run();
+
+            assert_eq!(fast, reference, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn agrees_on_standard_deals() {
+        agrees_with_game(DeckSpec::default(), 2, Rules::default(), 500);
+    }
+
+    #[test]
+    fn agrees_on_bigger_tables_under_other_rules() {
+        let rules = Rules {
+            leader: Leader::Legacy,
+            out_of_cards: OutOfCards::Forfeit,
+            ..Rules::default()
+        };
+        agrees_with_game(DeckSpec::default(), 3, rules, 300);
+        agrees_with_game(DeckSpec::default(), 5, rules, 300);
+        agrees_with_game(DeckSpec::default(), 4, Rules::default(), 300);
+    }
+
+    #[test]
+    fn agrees_on_stripped_decks() {
+        for pack in [Pack::Piquet, Pack::Euchre] {
+            let deck = DeckSpec::Packs {
+                pack,
+                packs: 1,
+                jokers: 0,
+                joker_penalty: 0,
+            };
+            agrees_with_game(deck, 2, Rules::default(), 300);
+        }
+    }
+
+    #[test]
+    fn ends_when_nobody_holds_cards() {
+        let result = Engine::new().play(&[&[], &[]], &Rules::default(), Player::TWO);
+        assert_eq!(result.outcome, GameOutcome::Winner(Player::TWO));
+    }
+
+    #[test]
+    fn plays_manns_record_deal() {
+        let deal = "K-KK----K-A-----JAA--Q--J-/---Q---Q-J-----J------AQ--"
+            .parse::<Deal>()
+            .unwrap();
+        let result = play(&deal, &Rules::default());
+        assert_eq!(result.outcome, GameOutcome::Winner(Player::TWO));
+        assert_eq!(result.cards_played, 7157);
+        assert_eq!(result.tricks, 1007);
+    }
+
+    #[test]
+    fn agrees_on_the_published_infinite_deal() {
+        let rules = Rules::default();
+        let deal = "---K---Q-KQAJ-----AAJ--J--/----------Q----KQ-J-----KA"
+            .parse::<Deal>()
+            .unwrap();
+        let hands = deal.to_cards(&crate::create_deck(), &rules).unwrap();
+        assert_eq!(play(&deal, &rules), Game::new(hands).run());
+    }
+}
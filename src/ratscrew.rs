@@ -0,0 +1,170 @@
+// CRATES
+
+use crate::events::EventSink;
+use crate::seed::{SeededRng, ShuffleVersion};
+use crate::{Game, GameResult, Player};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// CONSTANTS
+
+/// Mixed into the game seed so the slappers draw from a different stream
+/// than the shuffle.
+const SEED_SALT: u64 = 0x5a1f_5a1f_5a1f_5a1f;
+
+// STRUCTS + METHODS
+
+/// How long a simulated player takes to slap, in milliseconds: normally
+/// distributed around `mean` with standard deviation `spread`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Reaction {
+    pub mean: f64,
+    pub spread: f64,
+}
+
+impl Default for Reaction {
+    fn default() -> Self {
+        Reaction {
+            mean: 300.0,
+            spread: 60.0,
+        }
+    }
+}
+
+impl fmt::Display for Reaction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}ms ± {}ms", self.mean, self.spread)
+    }
+}
+
+impl FromStr for Reaction {
+    type Err = String;
+
+    /// Parse `mean` or `mean:spread`; the spread defaults to a fifth of the
+    /// mean.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid reaction time '{}', expected <ms> or <ms>:<ms>", s);
+        let parse = |value: &str| {
+            value
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite() && *value >= 0.0)
+                .ok_or_else(invalid)
+        };
+
+        let (mean, spread) = match s.split_once(':') {
+            Some((mean, spread)) => (parse(mean)?, parse(spread)?),
+            None => {
+                let mean = parse(s)?;
+                (mean, mean / 5.0)
+            }
+        };
+        Ok(Reaction { mean, spread })
+    }
+}
+
+/// A simulated player watching the pile.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Agent {
+    pub reaction: Reaction,
+    /// The chance of spotting a pile that can be slapped.
+    pub accuracy: f64,
+    /// The chance of slapping after a card that allows no slap.
+    pub false_slaps: f64,
+}
+
+impl Default for Agent {
+    fn default() -> Self {
+        Agent {
+            reaction: Reaction::default(),
+            accuracy: 0.9,
+            false_slaps: 0.01,
+        }
+    }
+}
+
+/// The agents at the table and the random stream their reactions are drawn
+/// from. Seats without an agent never slap on their own.
+#[derive(Debug, Clone)]
+pub struct Slappers {
+    agents: Vec<Option<Agent>>,
+    rng: SeededRng,
+}
+
+impl Slappers {
+    /// Seat `agents` in order, drawing their reactions from `seed` with the
+    /// random stream of `version`.
+    pub fn new(agents: &[Agent], seed: u64, version: ShuffleVersion) -> Self {
+        Slappers {
+            agents: agents.iter().copied().map(Some).collect(),
+            rng: version.rng(seed ^ SEED_SALT),
+        }
+    }
+
+    /// Leave `player`'s seat to someone else, such as a human at the
+    /// keyboard.
+    pub fn without(mut self, player: Player) -> Self {
+        if let Some(agent) = self.agents.get_mut(player.index()) {
+            *agent = None;
+        }
+        self
+    }
+
+    /// Who slaps first after the card just played, if anyone, and after how
+    /// many milliseconds. Ties go to the earlier seat.
+    pub fn react<S: EventSink>(&mut self, game: &Game<S>) -> Option<(Player, f64)> {
+        if game.central_pile().is_empty() || !game.rules().slaps.enabled() {
+            return None;
+        }
+
+        let slappable = game.slappable().is_some();
+        let mut fastest: Option<(Player, f64)> = None;
+
+        for player in Player::all(game.players()) {
+            let Some(agent) = self.agents.get(player.index()).copied().flatten() else {
+                continue;
+            };
+            if !game.is_live(player) {
+                continue;
+            }
+
+            let chance = if slappable {
+                agent.accuracy
+            } else {
+                agent.false_slaps
+            };
+            if !self.rng.chance(chance) {
+                continue;
+            }
+
+            let time = self
+                .rng
+                .normal(agent.reaction.mean, agent.reaction.spread)
+                .max(0.0);
+            if fastest.is_none_or(|(_, best)| time < best) {
+                fastest = Some((player, time));
+            }
+        }
+
+        fastest
+    }
+}
+
+// HELPER FUNCTIONS
+
+/// Play Egyptian Ratscrew to the end: after every card the fastest agent
+/// to react slaps the pile, taking it if the slap rules allow and burning
+/// cards if not.
+pub fn play<S: EventSink>(game: &mut Game<S>, slappers: &mut Slappers) -> GameResult {
+    loop {
+        if let Some(result) = game.step() {
+            break result;
+        }
+        if let Some((player, _)) = slappers.react(game) {
+            if let Some(result) = game.slap(player) {
+                break result;
+            }
+        }
+    }
+}
@@ -0,0 +1,385 @@
+// CRATES
+
+use crate::{Card, Rank};
+use serde::de::Error as _;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+// STRUCTS + METHODS
+
+/// Who leads the next trick once a penalty phase has been paid in full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Leader {
+    /// The player who collects the pile leads, as the standard rules say.
+    #[default]
+    Collector,
+    /// The player who opened the penalty phase leads again, whoever collected.
+    /// Games saved before the leader became configurable were played this way.
+    Legacy,
+}
+
+impl fmt::Display for Leader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Leader::Collector => "collector",
+                Leader::Legacy => "legacy",
+            }
+        )
+    }
+}
+
+impl FromStr for Leader {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "collector" => Ok(Leader::Collector),
+            "legacy" => Ok(Leader::Legacy),
+            _ => Err(format!(
+                "unknown leader '{}', expected collector or legacy",
+                s
+            )),
+        }
+    }
+}
+
+/// What happens when the player paying a penalty has no cards left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutOfCards {
+    /// The payer drops out and the next player still in pays the rest.
+    #[default]
+    Pass,
+    /// The payer drops out and the player who laid the penalty card takes
+    /// the pile straight away.
+    Forfeit,
+}
+
+impl fmt::Display for OutOfCards {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                OutOfCards::Pass => "pass",
+                OutOfCards::Forfeit => "forfeit",
+            }
+        )
+    }
+}
+
+impl FromStr for OutOfCards {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pass" => Ok(OutOfCards::Pass),
+            "forfeit" => Ok(OutOfCards::Forfeit),
+            _ => Err(format!(
+                "unknown out-of-cards rule '{}', expected pass or forfeit",
+                s
+            )),
+        }
+    }
+}
+
+/// How many cards each rank demands when played. Jokers carry their own
+/// penalty and are not listed.
+///
+/// Written as a map from rank to penalty, e.g. `{ J = 1, Q = 2, K = 3, A = 4 }`
+/// in TOML; ranks left out are not penalty cards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Penalties([u8; 13]);
+
+impl Penalties {
+    pub fn get(&self, rank: Rank) -> Option<u8> {
+        match rank {
+            Rank::Joker(_) => rank.penalty_value(),
+            _ => Some(self.0[rank.index() as usize]).filter(|&penalty| penalty > 0),
+        }
+    }
+
+    /// Make `rank` demand `penalty` cards, or stop it being a penalty card
+    /// if `penalty` is zero.
+    pub fn set(&mut self, rank: Rank, penalty: u8) {
+        if !matches!(rank, Rank::Joker(_)) {
+            self.0[rank.index() as usize] = penalty;
+        }
+    }
+}
+
+impl Default for Penalties {
+    /// Jacks, queens, kings and aces demand one to four cards.
+    fn default() -> Self {
+        let mut penalties = Penalties([0; 13]);
+        for rank in Rank::ALL {
+            penalties.set(rank, rank.penalty_value().unwrap_or(0));
+        }
+        penalties
+    }
+}
+
+impl fmt::Display for Penalties {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = true;
+        for rank in Rank::ALL {
+            if let Some(penalty) = self.get(rank) {
+                write!(f, "{}{}={}", if first { "" } else { " " }, rank, penalty)?;
+                first = false;
+            }
+        }
+        if first {
+            write!(f, "none")?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Penalties {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let ranks = Rank::ALL
+            .into_iter()
+            .filter_map(|rank| Some((rank.to_string(), self.get(rank)?)))
+            .collect::<Vec<_>>();
+        let mut map = serializer.serialize_map(Some(ranks.len()))?;
+        for (rank, penalty) in ranks {
+            map.serialize_entry(&rank, &penalty)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Penalties {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut penalties = Penalties([0; 13]);
+        for (rank, penalty) in HashMap::<String, u8>::deserialize(deserializer)? {
+            let rank = rank.parse::<Rank>().map_err(D::Error::custom)?;
+            if matches!(rank, Rank::Joker(_)) {
+                return Err(D::Error::custom(
+                    "jokers carry their own penalty and cannot be listed",
+                ));
+            }
+            // Rank-only deals write a penalty as a single digit.
+            if penalty > Rank::MAX_JOKER_PENALTY {
+                return Err(D::Error::custom(format!(
+                    "{} cannot demand {} cards, the most is {}",
+                    rank,
+                    penalty,
+                    Rank::MAX_JOKER_PENALTY
+                )));
+            }
+            penalties.set(rank, penalty);
+        }
+        Ok(penalties)
+    }
+}
+
+/// A pattern on top of the central pile that lets a player slap it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Slap {
+    Double,
+    Sandwich,
+    TopBottom,
+    Marriage,
+}
+
+impl fmt::Display for Slap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Slap::Double => "double",
+                Slap::Sandwich => "sandwich",
+                Slap::TopBottom => "top-bottom",
+                Slap::Marriage => "marriage",
+            }
+        )
+    }
+}
+
+/// Egyptian Ratscrew slaps: which patterns on top of the central pile let a
+/// player slap it and take it, and what a wrong slap costs. All off by
+/// default, which is plain beggar-my-neighbour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SlapRules {
+    /// Two cards of the same rank in a row.
+    pub doubles: bool,
+    /// Two cards of the same rank with one other card between them.
+    pub sandwiches: bool,
+    /// The card just played matches the bottom card of the pile.
+    pub top_bottom: bool,
+    /// A king and a queen in a row, in either order.
+    pub marriages: bool,
+    /// Cards a player puts under the pile for slapping when nothing matches.
+    pub burn: u8,
+}
+
+impl SlapRules {
+    /// Every slap in the standard Egyptian Ratscrew rules, with one card
+    /// burned for a false slap.
+    pub fn ratscrew() -> Self {
+        SlapRules {
+            doubles: true,
+            sandwiches: true,
+            top_bottom: true,
+            marriages: true,
+            burn: 1,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.doubles || self.sandwiches || self.top_bottom || self.marriages
+    }
+
+    /// The slap `pile` allows, if any, looking at the card just played
+    /// (the last in `pile`) and those under it.
+    pub fn check(&self, pile: &[Card]) -> Option<Slap> {
+        let rank = |depth: usize| pile.len().checked_sub(depth + 1).map(|i| pile[i].rank);
+        let top = rank(0)?;
+
+        if self.doubles && rank(1) == Some(top) {
+            return Some(Slap::Double);
+        }
+        if self.sandwiches && rank(2) == Some(top) {
+            return Some(Slap::Sandwich);
+        }
+        if self.top_bottom && pile.len() > 1 && pile[0].rank == top {
+            return Some(Slap::TopBottom);
+        }
+        if self.marriages
+            && matches!(
+                (top, rank(1)),
+                (Rank::King, Some(Rank::Queen)) | (Rank::Queen, Some(Rank::King))
+            )
+        {
+            return Some(Slap::Marriage);
+        }
+        None
+    }
+}
+
+impl fmt::Display for SlapRules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.enabled() {
+            return write!(f, "none");
+        }
+
+        let slaps = [
+            (self.doubles, "doubles"),
+            (self.sandwiches, "sandwiches"),
+            (self.top_bottom, "top-bottom"),
+            (self.marriages, "marriages"),
+        ];
+        let names = slaps
+            .iter()
+            .filter(|(on, _)| *on)
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>();
+        write!(f, "{}, burn {}", names.join(", "), self.burn)
+    }
+}
+
+/// Everything about how a game is played that is not fixed by the deal.
+/// Fields missing from a rules file or saved record take their defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Rules {
+    pub leader: Leader,
+    pub penalties: Penalties,
+    pub out_of_cards: OutOfCards,
+    pub slaps: SlapRules,
+}
+
+impl Rules {
+    /// The rules every game was played under before they became configurable.
+    pub fn legacy() -> Self {
+        Rules {
+            leader: Leader::Legacy,
+            ..Rules::default()
+        }
+    }
+
+    /// Read a rules file written in TOML, e.g.
+    ///
+    /// ```toml
+    /// leader = "collector"
+    /// out-of-cards = "forfeit"
+    ///
+    /// [penalties]
+    /// 10 = 5
+    /// J = 1
+    /// Q = 2
+    /// K = 3
+    /// A = 4
+    /// ```
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        toml::from_str(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// How many cards `rank` demands, if it is a penalty card at all.
+    pub fn penalty(&self, rank: Rank) -> Option<u8> {
+        self.penalties.get(rank)
+    }
+}
+
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "leader after penalty: {}, penalties: {}, out of cards: {}, slaps: {}",
+            self.leader, self.penalties, self.out_of_cards, self.slaps
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_rules_files() {
+        let rules = toml::from_str::<Rules>(
+            "leader = \"legacy\"\nout-of-cards = \"forfeit\"\n\n[penalties]\n10 = 5\nJ = 1\nQ = 2\nK = 3\nA = 4\n",
+        )
+        .unwrap();
+        assert_eq!(rules.leader, Leader::Legacy);
+        assert_eq!(rules.out_of_cards, OutOfCards::Forfeit);
+        assert_eq!(rules.penalty(Rank::Ten), Some(5));
+        assert_eq!(rules.penalty(Rank::Ace), Some(4));
+        assert_eq!(rules.penalty(Rank::Nine), None);
+        assert_eq!(rules.penalty(Rank::Joker(3)), Some(3));
+        assert_eq!(toml::from_str(&toml::to_string(&rules).unwrap()), Ok(rules));
+
+        assert_eq!(toml::from_str::<Rules>(""), Ok(Rules::default()));
+        let only_kings = toml::from_str::<Rules>("[penalties]\nK = 3").unwrap();
+        assert_eq!(only_kings.penalties.to_string(), "K=3");
+        assert_eq!(only_kings.leader, Leader::Collector);
+    }
+
+    #[test]
+    fn refuses_penalties_deals_cannot_write() {
+        for text in [
+            "[penalties]\nK = 10",
+            "[penalties]\n\"*\" = 2",
+            "[penalties]\nX = 1",
+            "leader = \"dealer\"",
+            "unknown = true",
+        ] {
+            assert!(toml::from_str::<Rules>(text).is_err(), "{}", text);
+        }
+    }
+}
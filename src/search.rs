@@ -0,0 +1,308 @@
+// CRATES
+
+use crate::deal::stable_hash;
+use crate::rank_game::Engine;
+use crate::rules::Rules;
+use crate::{CanonicalDeal, Deal, DeckSpec, GameOutcome, GameResult, Player};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+// CONSTANTS
+
+/// Deals each worker remembers the results of before starting afresh.
+const MAX_PLAYED: usize = 1 << 18;
+
+// STRUCTS + METHODS
+
+/// What the search is trying to make as long as possible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Metric {
+    Tricks,
+    #[default]
+    Cards,
+}
+
+impl Metric {
+    pub fn score(&self, result: &GameResult) -> usize {
+        match self {
+            Metric::Tricks => result.tricks,
+            Metric::Cards => result.cards_played,
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Metric::Tricks => "tricks",
+                Metric::Cards => "cards",
+            }
+        )
+    }
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tricks" => Ok(Metric::Tricks),
+            "cards" => Ok(Metric::Cards),
+            _ => Err(format!("unknown metric '{}', expected tricks or cards", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchConfig {
+    pub rules: Rules,
+    pub deck: DeckSpec,
+    pub metric: Metric,
+    pub threads: usize,
+    /// Total random restarts across all threads, or `None` to search forever.
+    pub restarts: Option<u64>,
+    /// Card swaps tried from each random restart.
+    pub iterations: u64,
+    /// Starting temperature for simulated annealing, in units of the metric.
+    /// It falls linearly to zero, so zero gives plain hill-climbing.
+    pub temperature: f64,
+    pub top: usize,
+    pub seed: u64,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        SearchConfig {
+            rules: Rules::default(),
+            deck: DeckSpec::default(),
+            metric: Metric::default(),
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            restarts: None,
+            iterations: 20_000,
+            temperature: 50.0,
+            top: 10,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub deal: Deal,
+    pub result: GameResult,
+}
+
+/// The longest games found so far by tricks and by cards, plus every deal
+/// found to cycle forever.
+#[derive(Debug, Clone, Default)]
+pub struct Leaderboard {
+    size: usize,
+    by_tricks: Vec<Entry>,
+    by_cards: Vec<Entry>,
+    infinite: Vec<Entry>,
+}
+
+impl Leaderboard {
+    pub fn new(size: usize) -> Self {
+        Leaderboard {
+            size,
+            ..Leaderboard::default()
+        }
+    }
+
+    pub fn by_tricks(&self) -> &[Entry] {
+        &self.by_tricks
+    }
+
+    pub fn by_cards(&self) -> &[Entry] {
+        &self.by_cards
+    }
+
+    pub fn infinite(&self) -> &[Entry] {
+        &self.infinite
+    }
+
+    /// Add a game if it makes either board or cycles forever. Returns whether
+    /// the leaderboard changed.
+    pub fn offer(&mut self, deal: &Deal, result: &GameResult) -> bool {
+        if let GameOutcome::Infinite { .. } = result.outcome {
+            if self.infinite.iter().any(|entry| entry.deal == *deal) {
+                return false;
+            }
+            self.infinite.push(Entry {
+                deal: deal.clone(),
+                result: *result,
+            });
+            return true;
+        }
+
+        let by_tricks = insert(&mut self.by_tricks, self.size, deal, result, Metric::Tricks);
+        let by_cards = insert(&mut self.by_cards, self.size, deal, result, Metric::Cards);
+        by_tricks || by_cards
+    }
+}
+
+// HELPER FUNCTIONS
+
+/// Search for long games on every thread in `config`, calling `on_update`
+/// with the leaderboard each time it changes. Returns the final leaderboard.
+///
+/// Each restart deals a random deck from `config.deck`, then anneals it by swapping
+/// pairs of cards with different penalty values. Improvements on the current
+/// deal and every infinite game are offered to the leaderboard.
+pub fn search<F>(config: &SearchConfig, on_update: F) -> Leaderboard
+where
+    F: Fn(&Leaderboard) + Sync,
+{
+    let leaderboard = Mutex::new(Leaderboard::new(config.top));
+    let restarts = AtomicU64::new(0);
+
+    thread::scope(|scope| {
+        for thread_idx in 0..config.threads.max(1) {
+            let leaderboard = &leaderboard;
+            let restarts = &restarts;
+            let on_update = &on_update;
+
+            scope.spawn(move || {
+                let mut rng = StdRng::seed_from_u64(config.seed.wrapping_add(thread_idx as u64));
+                let mut engine = Engine::new();
+
+                while config
+                    .restarts
+                    .is_none_or(|limit| restarts.fetch_add(1, Ordering::Relaxed) < limit)
+                {
+                    anneal(config, &mut rng, &mut engine, |deal, result| {
+                        let mut board = leaderboard.lock().unwrap();
+                        if board.offer(deal, result) {
+                            on_update(&board);
+                        }
+                    });
+                }
+            });
+        }
+    });
+
+    leaderboard.into_inner().unwrap()
+}
+
+fn anneal<R, F>(config: &SearchConfig, rng: &mut R, engine: &mut Engine, mut offer: F)
+where
+    R: Rng,
+    F: FnMut(&Deal, &GameResult),
+{
+    let mut cards = penalties(&config.deck, &config.rules);
+    cards.shuffle(rng);
+
+    let split_point = cards.len() / 2;
+    // Late in annealing most swaps are undone, so the same neighbours come
+    // up again and again. Deals are found by their stable hash, which needs
+    // no deal built, and checked before a result is reused.
+    let mut played = HashMap::<u64, (CanonicalDeal, GameResult)>::new();
+    let mut evaluate = |cards: &[u8]| {
+        let (first, second) = cards.split_at(split_point);
+        let hands = [first, second];
+        let hash = stable_hash(Player::ONE, hands);
+        if let Some((deal, result)) = played.get(&hash) {
+            if deal.holds(&hands, Player::ONE) {
+                return *result;
+            }
+        }
+
+        if played.len() >= MAX_PLAYED {
+            played.clear();
+        }
+        let result = engine.play(&hands, &config.rules, Player::ONE);
+        let deal = CanonicalDeal::new(to_deal(cards, split_point), Player::ONE);
+        played.insert(hash, (deal, result));
+        result
+    };
+
+    let result = evaluate(&cards);
+    offer(&to_deal(&cards, split_point), &result);
+    let mut score = config.metric.score(&result);
+
+    for iteration in 0..config.iterations {
+        let i = rng.random_range(0..cards.len());
+        let j = rng.random_range(0..cards.len());
+        if cards[i] == cards[j] {
+            continue;
+        }
+
+        cards.swap(i, j);
+        let candidate = evaluate(&cards);
+        let candidate_score = match candidate.outcome {
+            GameOutcome::Winner(_) => config.metric.score(&candidate),
+            GameOutcome::Infinite { .. } => 0,
+        };
+
+        let progress = iteration as f64 / config.iterations as f64;
+        let temperature = config.temperature * (1.0 - progress);
+        let accept = candidate_score >= score
+            || (temperature > 0.0
+                && rng.random::<f64>()
+                    < ((candidate_score as f64 - score as f64) / temperature).exp());
+
+        if candidate.winner().is_none() || candidate_score > score {
+            offer(&to_deal(&cards, split_point), &candidate);
+        }
+
+        if accept {
+            score = candidate_score;
+        } else {
+            cards.swap(i, j);
+        }
+    }
+}
+
+fn insert(
+    board: &mut Vec<Entry>,
+    size: usize,
+    deal: &Deal,
+    result: &GameResult,
+    metric: Metric,
+) -> bool {
+    let score = metric.score(result);
+    if board.iter().any(|entry| entry.deal == *deal)
+        || (board.len() >= size
+            && board
+                .last()
+                .is_some_and(|e| metric.score(&e.result) >= score))
+    {
+        return false;
+    }
+
+    let position = board
+        .iter()
+        .position(|entry| metric.score(&entry.result) < score)
+        .unwrap_or(board.len());
+    board.insert(
+        position,
+        Entry {
+            deal: deal.clone(),
+            result: *result,
+        },
+    );
+    board.truncate(size);
+    position < size
+}
+
+fn penalties(deck: &DeckSpec, rules: &Rules) -> Vec<u8> {
+    deck.cards()
+        .iter()
+        .map(|card| rules.penalty(card.rank).unwrap_or(0))
+        .collect()
+}
+
+fn to_deal(cards: &[u8], split_point: usize) -> Deal {
+    let (first, second) = cards.split_at(split_point);
+    Deal::new(vec![first.to_vec(), second.to_vec()])
+}
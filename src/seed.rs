@@ -0,0 +1,241 @@
+// CRATES
+
+use crate::{create_deck, Card};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use rand_chacha::ChaCha8Rng;
+use rand_distr::Normal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// CONSTANTS
+
+/// Where a 64-bit FNV-1a hash starts.
+pub(crate) const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// STRUCTS + METHODS
+
+/// How a seed becomes a random stream and a shuffled deck. Saved games keep
+/// their version so their seeds deal the same cards after upgrades.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShuffleVersion {
+    /// `rand` 0.9's `StdRng::seed_from_u64` and `SliceRandom::shuffle`.
+    /// Neither is promised to stay the same across `rand` releases, so this
+    /// only reproduces while that dependency is pinned.
+    V1,
+    /// ChaCha8 keyed with the seed's eight little-endian bytes followed by
+    /// zeros, shuffled by Fisher–Yates from the last card down, each swap
+    /// drawing a 32-bit word and reducing it without bias by Lemire's
+    /// multiply-and-reject method. Slapper reactions draw from the same
+    /// stream with the written-out draws of [`SeededRng::chance`] and
+    /// [`SeededRng::normal`].
+    #[default]
+    V2,
+}
+
+impl ShuffleVersion {
+    /// The version games saved before it was recorded were dealt with.
+    pub fn legacy() -> Self {
+        ShuffleVersion::V1
+    }
+
+    pub fn rng(&self, seed: u64) -> SeededRng {
+        match self {
+            ShuffleVersion::V1 => SeededRng::V1(StdRng::seed_from_u64(seed)),
+            ShuffleVersion::V2 => {
+                let mut key = [0; 32];
+                key[..8].copy_from_slice(&seed.to_le_bytes());
+                SeededRng::V2(ChaCha8Rng::from_seed(key))
+            }
+        }
+    }
+
+    /// Shuffle any deck from `seed`.
+    pub fn shuffle(&self, seed: u64, mut deck: Vec<Card>) -> Vec<Card> {
+        let mut rng = self.rng(seed);
+        match self {
+            ShuffleVersion::V1 => deck.shuffle(&mut rng),
+            ShuffleVersion::V2 => {
+                for i in (1..deck.len()).rev() {
+                    let j = below(&mut rng, i as u32 + 1);
+                    deck.swap(i, j as usize);
+                }
+            }
+        }
+        deck
+    }
+}
+
+impl fmt::Display for ShuffleVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ShuffleVersion::V1 => "v1",
+                ShuffleVersion::V2 => "v2",
+            }
+        )
+    }
+}
+
+impl FromStr for ShuffleVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "v1" => Ok(ShuffleVersion::V1),
+            "v2" => Ok(ShuffleVersion::V2),
+            _ => Err(format!(
+                "unknown shuffle version '{}', expected v1 or v2",
+                s
+            )),
+        }
+    }
+}
+
+/// The random stream behind a [`ShuffleVersion`].
+#[derive(Debug, Clone)]
+pub enum SeededRng {
+    V1(StdRng),
+    V2(ChaCha8Rng),
+}
+
+impl SeededRng {
+    /// Whether an event of probability `p` happens. Version 2 compares `p`
+    /// against a uniform number made of the top 53 bits of one 64-bit word;
+    /// version 1 uses `rand`'s `random_bool`.
+    pub(crate) fn chance(&mut self, p: f64) -> bool {
+        let p = p.clamp(0.0, 1.0);
+        match self {
+            SeededRng::V1(rng) => rng.random_bool(p),
+            SeededRng::V2(_) => unit(self) < p,
+        }
+    }
+
+    /// A normally distributed number. Version 2 uses the cosine half of the
+    /// Box–Muller transform over two 64-bit words, so the words drawn never
+    /// change, though `ln` and `cos` may differ in the last bit between
+    /// platforms; version 1 uses `rand_distr`'s `Normal`, falling back to
+    /// the mean when the spread is invalid.
+    pub(crate) fn normal(&mut self, mean: f64, spread: f64) -> f64 {
+        match self {
+            SeededRng::V1(rng) => {
+                Normal::new(mean, spread).map_or(mean, |normal| normal.sample(rng))
+            }
+            SeededRng::V2(_) => {
+                let radius = (-2.0 * (1.0 - unit(self)).ln()).sqrt();
+                let angle = std::f64::consts::TAU * unit(self);
+                mean + spread * radius * angle.cos()
+            }
+        }
+    }
+}
+
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            SeededRng::V1(rng) => rng.next_u32(),
+            SeededRng::V2(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            SeededRng::V1(rng) => rng.next_u64(),
+            SeededRng::V2(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        match self {
+            SeededRng::V1(rng) => rng.fill_bytes(dst),
+            SeededRng::V2(rng) => rng.fill_bytes(dst),
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+
+/// A uniform number in `[0, 1)` from the top 53 bits of one 64-bit word.
+fn unit(rng: &mut impl RngCore) -> f64 {
+    (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+pub fn random_seed() -> u64 {
+    rand::rng().next_u64()
+}
+
+/// Hash an arbitrary string to a seed with 64-bit FNV-1a, so text seeds stay
+/// stable across platforms and Rust versions.
+pub fn hash_seed_text(text: &str) -> u64 {
+    fnv1a(FNV_OFFSET, text.as_bytes())
+}
+
+/// Carry a 64-bit FNV-1a hash on over `bytes`, starting from [`FNV_OFFSET`]
+/// for a fresh hash.
+pub(crate) fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// A standard deck shuffled from `seed` with the current shuffle.
+pub fn shuffled_deck(seed: u64) -> Vec<Card> {
+    ShuffleVersion::default().shuffle(seed, create_deck())
+}
+
+/// A uniform number below `bound` by Lemire's multiply-and-reject method, so
+/// the result depends only on the words drawn.
+pub(crate) fn below(rng: &mut impl RngCore, bound: u32) -> u32 {
+    let threshold = bound.wrapping_neg() % bound;
+    loop {
+        let product = rng.next_u32() as u64 * bound as u64;
+        if product as u32 >= threshold {
+            return (product >> 32) as u32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seed 7's first three reaction times around 300ms ± 60ms, after eight
+    /// even chances.
+    const PINNED_TIMES: [f64; 3] = [378.1345049607093, 251.47819873215434, 317.0192283516389];
+
+    #[test]
+    fn hashes_seed_text_with_fnv1a() {
+        assert_eq!(hash_seed_text(""), FNV_OFFSET);
+        assert_eq!(hash_seed_text("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash_seed_text("foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn draws_reactions_from_the_stream() {
+        let mut rng = ShuffleVersion::V2.rng(7);
+        let chances: Vec<bool> = (0..8).map(|_| rng.chance(0.5)).collect();
+        let times: Vec<f64> = (0..3).map(|_| rng.normal(300.0, 60.0)).collect();
+        assert_eq!(chances, [false, true, true, true, true, true, true, true]);
+        for (time, pinned) in times.iter().zip(PINNED_TIMES) {
+            assert!(
+                (time - pinned).abs() < 1e-9,
+                "{} drifted from {}",
+                time,
+                pinned
+            );
+        }
+    }
+
+    #[test]
+    fn chances_of_nothing_and_everything() {
+        let mut rng = ShuffleVersion::V2.rng(1);
+        assert!((0..1000).all(|_| rng.chance(1.0)));
+        assert!((0..1000).all(|_| !rng.chance(0.0)));
+        assert!((0..1000).all(|_| rng.normal(300.0, 0.0) == 300.0));
+    }
+}
@@ -0,0 +1,214 @@
+// CRATES
+
+use crate::{GameResult, Player};
+
+// STRUCTS + METHODS
+
+/// Counts of a whole-number measurement kept as a histogram indexed by
+/// value, so memory stays bounded however many games are recorded.
+#[derive(Debug, Clone, Default)]
+pub struct Distribution {
+    count: u64,
+    total: u64,
+    counts: Vec<u64>,
+}
+
+impl Distribution {
+    pub fn record(&mut self, value: usize) {
+        self.count += 1;
+        self.total += value as u64;
+
+        if self.counts.len() <= value {
+            self.counts.resize(value + 1, 0);
+        }
+        self.counts[value] += 1;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        self.total as f64 / self.count as f64
+    }
+
+    pub fn median(&self) -> usize {
+        let middle = self.count.div_ceil(2);
+        let mut seen = 0;
+
+        for (value, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= middle && count > 0 {
+                return value;
+            }
+        }
+        0
+    }
+
+    /// Add every value recorded in `other`.
+    pub fn merge(&mut self, other: &Distribution) {
+        self.count += other.count;
+        self.total += other.total;
+
+        if self.counts.len() < other.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+        for (mine, theirs) in self.counts.iter_mut().zip(&other.counts) {
+            *mine += theirs;
+        }
+    }
+
+    pub fn max(&self) -> usize {
+        self.counts.len().saturating_sub(1)
+    }
+
+    /// Counts grouped into buckets of `width`, as
+    /// `(smallest value in bucket, count)` pairs.
+    pub fn histogram(&self, width: usize) -> Vec<(usize, u64)> {
+        let width = width.max(1);
+        self.counts
+            .chunks(width)
+            .enumerate()
+            .map(|(i, chunk)| (i * width, chunk.iter().sum()))
+            .collect()
+    }
+}
+
+/// Running totals over many games.
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    games: u64,
+    wins: Vec<u64>,
+    /// Games won by the player who led the first trick.
+    leader_wins: u64,
+    infinite: u64,
+    tricks: Distribution,
+    cards_played: Distribution,
+}
+
+impl Stats {
+    pub fn new(players: usize) -> Self {
+        Stats {
+            wins: vec![0; players],
+            ..Stats::default()
+        }
+    }
+
+    /// Count a game that `first` led.
+    pub fn record(&mut self, result: &GameResult, first: Player) {
+        self.games += 1;
+        match result.winner() {
+            Some(winner) => {
+                self.wins[winner.index()] += 1;
+                if winner == first {
+                    self.leader_wins += 1;
+                }
+            }
+            None => self.infinite += 1,
+        }
+        self.tricks.record(result.tricks);
+        self.cards_played.record(result.cards_played);
+    }
+
+    /// Add the games counted in `other`, which must be for the same number
+    /// of players.
+    pub fn merge(&mut self, other: &Stats) {
+        self.games += other.games;
+        for (mine, theirs) in self.wins.iter_mut().zip(&other.wins) {
+            *mine += theirs;
+        }
+        self.leader_wins += other.leader_wins;
+        self.infinite += other.infinite;
+        self.tricks.merge(&other.tricks);
+        self.cards_played.merge(&other.cards_played);
+    }
+
+    pub fn games(&self) -> u64 {
+        self.games
+    }
+
+    pub fn players(&self) -> usize {
+        self.wins.len()
+    }
+
+    pub fn wins(&self, player: Player) -> u64 {
+        self.wins[player.index()]
+    }
+
+    pub fn leader_wins(&self) -> u64 {
+        self.leader_wins
+    }
+
+    pub fn infinite(&self) -> u64 {
+        self.infinite
+    }
+
+    pub fn win_rate(&self, player: Player) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        self.wins(player) as f64 / self.games as f64
+    }
+
+    /// The share of games won by whoever led the first trick.
+    pub fn leader_win_rate(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        self.leader_wins as f64 / self.games as f64
+    }
+
+    pub fn tricks(&self) -> &Distribution {
+        &self.tricks
+    }
+
+    pub fn cards_played(&self) -> &Distribution {
+        &self.cards_played
+    }
+}
+
+/// How deals turned out when each was played once with every player leading.
+#[derive(Debug, Clone, Default)]
+pub struct Orientations {
+    deals: u64,
+    same_winner: u64,
+    leader_won: u64,
+}
+
+impl Orientations {
+    /// Count one deal from its `results`, the first led by player one, the
+    /// next by player two and so on.
+    pub fn record(&mut self, results: &[GameResult]) {
+        self.deals += 1;
+        let winners = results.iter().map(GameResult::winner).collect::<Vec<_>>();
+
+        if winners[0].is_some() && winners.iter().all(|&winner| winner == winners[0]) {
+            self.same_winner += 1;
+        } else if (winners.iter().enumerate()).all(|(i, &winner)| winner == Some(Player::new(i))) {
+            self.leader_won += 1;
+        }
+    }
+
+    pub fn deals(&self) -> u64 {
+        self.deals
+    }
+
+    /// Deals won by the same player whoever led.
+    pub fn same_winner(&self) -> u64 {
+        self.same_winner
+    }
+
+    /// Deals won by whoever led, every time.
+    pub fn leader_won(&self) -> u64 {
+        self.leader_won
+    }
+
+    /// Deals whose winner changed with the leader some other way, or that
+    /// went on forever.
+    pub fn mixed(&self) -> u64 {
+        self.deals - self.same_winner - self.leader_won
+    }
+}
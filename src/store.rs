@@ -0,0 +1,315 @@
+// CRATES
+
+use crate::index::DealIndex;
+use crate::ratscrew::Agent;
+use crate::rules::{Leader, OutOfCards, Rules};
+use crate::seed::ShuffleVersion;
+use crate::{
+    split_deck, CanonicalDeal, Card, DealMethod, Equivalence, GameOutcome, GameResult, Player,
+};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+// CONSTANTS
+
+pub const DEFAULT_PATH: &str = "results.jsonl";
+
+// STRUCTS + METHODS
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub id: u64,
+    pub seed: Option<u64>,
+    /// How `seed` was turned into `starting_deck`. Records saved before the
+    /// version was kept used the first.
+    #[serde(default = "ShuffleVersion::legacy")]
+    pub shuffle: ShuffleVersion,
+    /// How the shuffled deck was dealt. Missing for fixed deals, and for
+    /// records saved before the method could be chosen, which were dealt
+    /// the `DealMethod::classic` way.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dealing: Option<DealMethod>,
+    /// Where the deal comes in the order of every rank-only deal, for
+    /// games dealt by index rather than from a seed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deal_index: Option<DealIndex>,
+    pub starting_deck: Vec<Card>,
+    /// Every player's hand as dealt, front of the hand first. Empty in
+    /// records saved before games could have more than two players, which
+    /// were all dealt by splitting `starting_deck` in half.
+    #[serde(default)]
+    pub initial_decks: Vec<Vec<Card>>,
+    #[serde(default = "Rules::legacy")]
+    pub rules: Rules,
+    /// Who led the first trick. Records saved before this was kept were
+    /// all led by player one.
+    #[serde(default)]
+    pub first: Player,
+    /// The simulated slappers in an Egyptian Ratscrew game, one per seat,
+    /// whose reactions are drawn from `seed`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agents: Vec<Agent>,
+    /// Records saved before infinite games were detected hold the winner
+    /// alone, under `winner`.
+    #[serde(alias = "winner", deserialize_with = "outcome_or_winner")]
+    pub outcome: GameOutcome,
+    pub cards_played: usize,
+    #[serde(default)]
+    pub tricks: usize,
+    #[serde(default)]
+    pub penalty_phases: usize,
+}
+
+impl Record {
+    /// Build a record ready to be appended; the store assigns its `id`.
+    pub fn new(
+        seed: Option<u64>,
+        starting_deck: &[Card],
+        initial_decks: &[VecDeque<Card>],
+        rules: Rules,
+        result: &GameResult,
+    ) -> Self {
+        Record {
+            id: 0,
+            seed,
+            shuffle: ShuffleVersion::default(),
+            dealing: None,
+            deal_index: None,
+            starting_deck: starting_deck.to_vec(),
+            initial_decks: initial_decks
+                .iter()
+                .map(|deck| deck.iter().copied().collect())
+                .collect(),
+            rules,
+            first: Player::ONE,
+            agents: Vec::new(),
+            outcome: result.outcome,
+            cards_played: result.cards_played,
+            tricks: result.tricks,
+            penalty_phases: result.penalty_phases,
+        }
+    }
+
+    /// The hands to replay this game from.
+    pub fn hands(&self) -> Vec<VecDeque<Card>> {
+        if self.initial_decks.is_empty() {
+            return split_deck(&self.starting_deck).into();
+        }
+        self.initial_decks
+            .iter()
+            .map(|deck| deck.iter().copied().collect())
+            .collect()
+    }
+
+    /// The game this record is of, as the game sees it, or `None` for an
+    /// Egyptian Ratscrew game, whose slaps depend on every card.
+    pub fn canonical(&self) -> Option<CanonicalDeal> {
+        if self.rules.slaps.enabled() || !self.agents.is_empty() {
+            return None;
+        }
+        Some(CanonicalDeal::from_cards(
+            &self.hands(),
+            &self.rules,
+            self.first,
+        ))
+    }
+
+    /// Whether a replayed game finished the way this record says it did.
+    /// Records saved before tricks and penalty phases were counted only
+    /// check the other fields.
+    pub fn matches(&self, result: &GameResult) -> bool {
+        self.outcome == result.outcome
+            && self.cards_played == result.cards_played
+            && (self.tricks == 0 || self.tricks == result.tricks)
+            && (self.penalty_phases == 0 || self.penalty_phases == result.penalty_phases)
+    }
+}
+
+/// A game as far as telling duplicates apart goes: the canonical deal and
+/// the rules its penalty values leave out.
+type GameKey = (CanonicalDeal, Leader, OutOfCards);
+
+/// An append-only JSON Lines file holding one [`Record`] per game.
+#[derive(Debug)]
+pub struct Store {
+    path: PathBuf,
+    file: File,
+    next_id: u64,
+    /// The id of the first record of every game saved, when duplicates are
+    /// being looked for.
+    saved: Option<(Equivalence, HashMap<GameKey, u64>)>,
+}
+
+impl Store {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let next_id = load(&path)?.last().map_or(1, |record| record.id + 1);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        Ok(Store {
+            path,
+            file,
+            next_id,
+            saved: None,
+        })
+    }
+
+    /// Keep track of the games already saved, so that [`Store::duplicate`]
+    /// can tell whether a record is of one of them under `equivalence`.
+    pub fn deduplicate(mut self, equivalence: Equivalence) -> io::Result<Self> {
+        let mut saved = HashMap::new();
+        for record in self.records()? {
+            if let Some(key) = key(&record, equivalence) {
+                saved.entry(key).or_insert(record.id);
+            }
+        }
+        self.saved = Some((equivalence, saved));
+        Ok(self)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&mut self, mut record: Record) -> io::Result<Record> {
+        record.id = self.next_id;
+
+        let line = serde_json::to_string(&record)?;
+        writeln!(self.file, "{}", line)?;
+
+        self.next_id += 1;
+        if let Some((equivalence, saved)) = &mut self.saved {
+            if let Some(key) = key(&record, *equivalence) {
+                saved.entry(key).or_insert(record.id);
+            }
+        }
+        Ok(record)
+    }
+
+    /// The id of a saved record of the same game as `record`, if duplicates
+    /// are being looked for and there is one.
+    pub fn duplicate(&self, record: &Record) -> Option<u64> {
+        let (equivalence, saved) = self.saved.as_ref()?;
+        saved.get(&key(record, *equivalence)?).copied()
+    }
+
+    pub fn records(&self) -> io::Result<Vec<Record>> {
+        load(&self.path)
+    }
+
+    /// Look a record up by id, falling back to the most recent game dealt
+    /// from that seed.
+    pub fn find(&self, key: u64) -> io::Result<Option<Record>> {
+        let records = self.records()?;
+
+        if let Some(record) = records.iter().find(|record| record.id == key) {
+            return Ok(Some(record.clone()));
+        }
+
+        Ok(records
+            .into_iter()
+            .rev()
+            .find(|record| record.seed == Some(key)))
+    }
+}
+
+// HELPER FUNCTIONS
+
+fn key(record: &Record, equivalence: Equivalence) -> Option<GameKey> {
+    let deal = equivalence.canonical(record.canonical()?);
+    Some((deal, record.rules.leader, record.rules.out_of_cards))
+}
+
+fn outcome_or_winner<'de, D: Deserializer<'de>>(deserializer: D) -> Result<GameOutcome, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Outcome(GameOutcome),
+        Winner(Player),
+    }
+
+    Ok(match Stored::deserialize(deserializer)? {
+        Stored::Outcome(outcome) => outcome,
+        Stored::Winner(player) => GameOutcome::Winner(player),
+    })
+}
+
+fn load(path: &Path) -> io::Result<Vec<Record>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+
+    let mut records = Vec::new();
+    for (line_number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record = serde_json::from_str(&line).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}:{}: {}", path.display(), line_number + 1, err),
+            )
+        })?;
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A game saved by the build that added batch runs, before infinite
+    /// games were detected.
+    const WINNER_ONLY: &str = r#"{"id":1,"seed":1,"starting_deck":["2♤","3♧","Q♡","10♡","3♢","4♤","7♧","5♢","8♢","2♧","8♧","A♤","Q♤","5♤","7♤","8♡","4♧","2♢","K♡","6♢","A♡","5♡","10♤","4♢","7♡","A♢","9♧","K♢","9♤","K♤","A♧","3♤","J♡","6♡","J♤","8♤","10♢","Q♧","Q♢","9♡","4♡","3♡","J♢","6♤","K♧","7♢","9♢","6♧","J♧","10♧","2♡","5♧"],"winner":"Two","turn_count":32,"cards_played":118}"#;
+
+    #[test]
+    fn loads_records_saved_with_a_winner() {
+        let record: Record = serde_json::from_str(WINNER_ONLY).unwrap();
+        assert_eq!(record.outcome, GameOutcome::Winner(Player::TWO));
+        assert_eq!(record.shuffle, ShuffleVersion::legacy());
+        assert_eq!(record.cards_played, 118);
+    }
+
+    #[test]
+    fn fills_in_fields_older_records_lack() {
+        let record: Record = serde_json::from_str(WINNER_ONLY).unwrap();
+        assert_eq!(record.seed, Some(1));
+        assert_eq!(record.rules, Rules::legacy());
+        assert_eq!(record.first, Player::ONE);
+        assert_eq!(record.dealing, None);
+        assert_eq!(record.deal_index, None);
+        assert!(record.initial_decks.is_empty());
+        assert!(record.agents.is_empty());
+        assert_eq!((record.tricks, record.penalty_phases), (0, 0));
+
+        let line = serde_json::to_string(&record).unwrap();
+        assert!(!line.contains("dealing") && !line.contains("agents"));
+    }
+
+    #[test]
+    fn loads_outcomes_naming_the_seat() {
+        let line = WINNER_ONLY.replace(r#""winner":"Two""#, r#""outcome":{"Winner":"Two"}"#);
+        let record: Record = serde_json::from_str(&line).unwrap();
+        assert_eq!(record.outcome, GameOutcome::Winner(Player::TWO));
+    }
+
+    #[test]
+    fn round_trips_outcomes() {
+        let mut record: Record = serde_json::from_str(WINNER_ONLY).unwrap();
+        record.outcome = GameOutcome::Infinite {
+            cycle_start: 3,
+            cycle_length: 7,
+        };
+        let line = serde_json::to_string(&record).unwrap();
+        assert_eq!(serde_json::from_str::<Record>(&line).unwrap(), record);
+    }
+}
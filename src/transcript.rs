@@ -0,0 +1,461 @@
+// CRATES
+
+use crate::events::{EventSink, GameEvent};
+use crate::ratscrew::Agent;
+use crate::rules::{Rules, Slap};
+use crate::seed::ShuffleVersion;
+use crate::store::Record;
+use crate::{print_cards, Card, Deal, DealMethod, GameOutcome, GameResult, Player};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+// CONSTANTS
+
+const CSV_HEADER: &str = "kind,trick,player,card,demands,to,owed,cards,note";
+
+// STRUCTS + METHODS
+
+/// The ways a transcript can be written out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One JSON object holding the whole transcript.
+    Json,
+    /// One row per card dealt, per move and for the result.
+    Csv,
+    /// Tag pairs and one line of moves per trick, after PGN.
+    Text,
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Format::Json => "json",
+                Format::Csv => "csv",
+                Format::Text => "text",
+            }
+        )
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "text" => Ok(Format::Text),
+            _ => Err(format!(
+                "unknown output format '{}', expected json, csv or text",
+                s
+            )),
+        }
+    }
+}
+
+/// The penalty a card was paid towards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Payment {
+    /// The player who laid the penalty card and collects if it is paid.
+    pub to: Player,
+    /// Cards still owed, counting this one.
+    pub owed: u8,
+}
+
+/// One thing that happened at the table. Every move carries the trick it
+/// belongs to, counting from one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Move {
+    Play {
+        trick: usize,
+        player: Player,
+        card: Card,
+        /// The cards the next player owes if this is a penalty card.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        demands: Option<u8>,
+        /// The penalty this card was paid towards, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pays: Option<Payment>,
+    },
+    Slap {
+        trick: usize,
+        player: Player,
+        slap: Slap,
+    },
+    FalseSlap {
+        trick: usize,
+        player: Player,
+        burned: Vec<Card>,
+    },
+    Collect {
+        trick: usize,
+        player: Player,
+        cards: Vec<Card>,
+    },
+    Eliminated {
+        trick: usize,
+        player: Player,
+    },
+}
+
+impl Move {
+    pub fn trick(&self) -> usize {
+        match *self {
+            Move::Play { trick, .. }
+            | Move::Slap { trick, .. }
+            | Move::FalseSlap { trick, .. }
+            | Move::Collect { trick, .. }
+            | Move::Eliminated { trick, .. } => trick,
+        }
+    }
+}
+
+/// A whole game written down: how it was dealt, every move and how it
+/// ended. Attach one to a [`Game`](crate::Game) as its sink to fill in the
+/// moves and result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub seed: Option<u64>,
+    pub shuffle: ShuffleVersion,
+    /// How the shuffled deck was dealt; missing for fixed deals.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dealing: Option<DealMethod>,
+    pub rules: Rules,
+    /// Who led the first trick.
+    #[serde(default)]
+    pub first: Player,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agents: Vec<Agent>,
+    pub starting_deck: Vec<Card>,
+    pub initial_decks: Vec<Vec<Card>>,
+    pub moves: Vec<Move>,
+    /// Missing until the game is over.
+    pub result: Option<GameResult>,
+}
+
+impl Transcript {
+    /// Start a transcript of the game dealt as `initial_decks`, with no moves
+    /// yet.
+    pub fn new(
+        seed: Option<u64>,
+        starting_deck: &[Card],
+        initial_decks: &[VecDeque<Card>],
+        rules: Rules,
+    ) -> Self {
+        Transcript {
+            seed,
+            shuffle: ShuffleVersion::default(),
+            dealing: None,
+            rules,
+            first: Player::ONE,
+            agents: Vec::new(),
+            starting_deck: starting_deck.to_vec(),
+            initial_decks: initial_decks
+                .iter()
+                .map(|deck| deck.iter().copied().collect())
+                .collect(),
+            moves: Vec::new(),
+            result: None,
+        }
+    }
+
+    pub fn write(&self, format: Format, mut writer: impl Write) -> io::Result<()> {
+        match format {
+            Format::Json => {
+                serde_json::to_writer(&mut writer, self)?;
+                writeln!(writer)?;
+            }
+            Format::Csv => self.write_csv(&mut writer)?,
+            Format::Text => self.write_text(&mut writer)?,
+        }
+        writer.flush()
+    }
+
+    fn write_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "{}", CSV_HEADER)?;
+        for card in &self.starting_deck {
+            writeln!(writer, "deck,,,{},,,,,", card)?;
+        }
+        for (player, deck) in Player::all(self.initial_decks.len()).zip(&self.initial_decks) {
+            for card in deck {
+                writeln!(writer, "hand,,{},{},,,,,", player.number(), card)?;
+            }
+        }
+        writeln!(writer, "first,,{},,,,,,", self.first.number())?;
+
+        for step in &self.moves {
+            match step {
+                Move::Play {
+                    trick,
+                    player,
+                    card,
+                    demands,
+                    pays,
+                } => writeln!(
+                    writer,
+                    "play,{},{},{},{},{},{},,",
+                    trick,
+                    player.number(),
+                    card,
+                    optional(*demands),
+                    optional(pays.map(|pays| pays.to.number())),
+                    optional(pays.map(|pays| pays.owed)),
+                )?,
+                Move::Slap {
+                    trick,
+                    player,
+                    slap,
+                } => writeln!(writer, "slap,{},{},,,,,,{}", trick, player.number(), slap)?,
+                Move::FalseSlap {
+                    trick,
+                    player,
+                    burned,
+                } => writeln!(
+                    writer,
+                    "false_slap,{},{},,,,,{},",
+                    trick,
+                    player.number(),
+                    print_cards(burned, " ")
+                )?,
+                Move::Collect {
+                    trick,
+                    player,
+                    cards,
+                } => writeln!(
+                    writer,
+                    "collect,{},{},,,,,{},",
+                    trick,
+                    player.number(),
+                    print_cards(cards, " ")
+                )?,
+                Move::Eliminated { trick, player } => {
+                    writeln!(writer, "eliminated,{},{},,,,,,", trick, player.number())?
+                }
+            }
+        }
+
+        if let Some(result) = &self.result {
+            let (winner, cycle) = match result.outcome {
+                GameOutcome::Winner(player) => (player.number().to_string(), String::new()),
+                GameOutcome::Infinite {
+                    cycle_start,
+                    cycle_length,
+                } => (
+                    String::new(),
+                    format!(" cycle_start={} cycle_length={}", cycle_start, cycle_length),
+                ),
+            };
+            writeln!(
+                writer,
+                "result,{},{},,,,,,cards_played={} penalty_phases={}{}",
+                result.tricks, winner, result.cards_played, result.penalty_phases, cycle
+            )?;
+        }
+        Ok(())
+    }
+
+    fn write_text(&self, mut writer: impl Write) -> io::Result<()> {
+        let game = if self.rules.slaps.enabled() {
+            "Egyptian Ratscrew"
+        } else {
+            "Beggar-my-neighbour"
+        };
+        let hands = self
+            .initial_decks
+            .iter()
+            .map(|deck| deck.iter().copied().collect())
+            .collect::<Vec<VecDeque<_>>>();
+
+        writeln!(writer, "[Game \"{}\"]", game)?;
+        if let Some(seed) = self.seed {
+            writeln!(writer, "[Seed \"{}\"]", seed)?;
+            writeln!(writer, "[Shuffle \"{}\"]", self.shuffle)?;
+        }
+        if let Some(dealing) = self.dealing {
+            writeln!(writer, "[DealMethod \"{}\"]", dealing)?;
+        }
+        writeln!(writer, "[Rules \"{}\"]", self.rules)?;
+        writeln!(writer, "[Players \"{}\"]", self.initial_decks.len())?;
+        writeln!(writer, "[First \"{}\"]", self.first.number())?;
+        writeln!(
+            writer,
+            "[StartingDeck \"{}\"]",
+            print_cards(&self.starting_deck, " ")
+        )?;
+        for (player, deck) in Player::all(self.initial_decks.len()).zip(&self.initial_decks) {
+            writeln!(
+                writer,
+                "[Hand{} \"{}\"]",
+                player.number(),
+                print_cards(deck, " ")
+            )?;
+        }
+        writeln!(
+            writer,
+            "[Deal \"{}\"]",
+            Deal::from_cards(&hands, &self.rules)
+        )?;
+        writeln!(writer, "[Result \"{}\"]", result_token(self.result))?;
+        if let Some(result) = &self.result {
+            writeln!(writer, "[CardsPlayed \"{}\"]", result.cards_played)?;
+            writeln!(writer, "[Tricks \"{}\"]", result.tricks)?;
+            writeln!(writer, "[PenaltyPhases \"{}\"]", result.penalty_phases)?;
+        }
+
+        let mut trick = 0;
+        for step in &self.moves {
+            if step.trick() != trick {
+                trick = step.trick();
+                write!(writer, "\n{}.", trick)?;
+            }
+            match step {
+                Move::Play {
+                    player,
+                    card,
+                    demands,
+                    ..
+                } => {
+                    write!(writer, " {}:{}", player.number(), card)?;
+                    if let Some(demands) = demands {
+                        write!(writer, "={}", demands)?;
+                    }
+                }
+                Move::Slap { player, slap, .. } => {
+                    write!(writer, " {{{} slaps a {}}}", player.number(), slap)?
+                }
+                Move::FalseSlap { player, burned, .. } => write!(
+                    writer,
+                    " {{{} burns {}}}",
+                    player.number(),
+                    print_cards(burned, " ")
+                )?,
+                Move::Collect { player, cards, .. } => {
+                    write!(writer, " {{{} takes {}}}", player.number(), cards.len())?
+                }
+                Move::Eliminated { player, .. } => {
+                    write!(writer, " {{{} is out}}", player.number())?
+                }
+            }
+        }
+        writeln!(writer, "\n{}", result_token(self.result))
+    }
+
+    /// The trick the next move belongs to.
+    fn trick(&self) -> usize {
+        match self.moves.last() {
+            Some(Move::Collect { trick, .. }) => trick + 1,
+            Some(step) => step.trick(),
+            None => 1,
+        }
+    }
+
+    /// The penalty the next card pays towards, worked out from the cards
+    /// before it.
+    fn payment(&self) -> Option<Payment> {
+        let last = self
+            .moves
+            .iter()
+            .rev()
+            .find(|step| !matches!(step, Move::FalseSlap { .. } | Move::Eliminated { .. }))?;
+        match *last {
+            Move::Play {
+                player,
+                demands: Some(demands),
+                ..
+            } => Some(Payment {
+                to: player,
+                owed: demands,
+            }),
+            Move::Play {
+                pays: Some(pays), ..
+            } if pays.owed > 1 => Some(Payment {
+                owed: pays.owed - 1,
+                ..pays
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl From<&Record> for Transcript {
+    /// The setup of a saved game, ready to replay into.
+    fn from(record: &Record) -> Self {
+        Transcript {
+            shuffle: record.shuffle,
+            dealing: record.dealing,
+            first: record.first,
+            agents: record.agents.clone(),
+            ..Transcript::new(
+                record.seed,
+                &record.starting_deck,
+                &record.hands(),
+                record.rules,
+            )
+        }
+    }
+}
+
+impl EventSink for Transcript {
+    fn emit(&mut self, event: &GameEvent) {
+        let trick = self.trick();
+        let step = match *event {
+            GameEvent::CardPlayed { player, card } => Move::Play {
+                trick,
+                player,
+                card,
+                demands: None,
+                pays: self.payment(),
+            },
+            GameEvent::PenaltyStarted { penalty, .. } => {
+                if let Some(Move::Play { demands, .. }) = self.moves.last_mut() {
+                    *demands = Some(penalty);
+                }
+                return;
+            }
+            GameEvent::Slapped { player, slap } => Move::Slap {
+                trick,
+                player,
+                slap,
+            },
+            GameEvent::FalseSlap { player, burned } => Move::FalseSlap {
+                trick,
+                player,
+                burned: burned.to_vec(),
+            },
+            GameEvent::PileCollected { player, cards } => Move::Collect {
+                trick,
+                player,
+                cards: cards.to_vec(),
+            },
+            GameEvent::PlayerEliminated { player } => Move::Eliminated { trick, player },
+            GameEvent::GameOver { result } => {
+                self.result = Some(result);
+                return;
+            }
+        };
+        self.moves.push(step);
+    }
+}
+
+// HELPER FUNCTIONS
+
+fn optional<T: fmt::Display>(value: Option<T>) -> String {
+    value.map_or_else(String::new, |value| value.to_string())
+}
+
+/// The winner's number, `infinite` for a game that never ends and `*` if the
+/// game is not over, in the style of a PGN result.
+fn result_token(result: Option<GameResult>) -> String {
+    match result.map(|result| result.outcome) {
+        Some(GameOutcome::Winner(player)) => player.number().to_string(),
+        Some(GameOutcome::Infinite { .. }) => "infinite".to_string(),
+        None => "*".to_string(),
+    }
+}
@@ -0,0 +1,312 @@
+// CRATES
+
+use beggar_my_neighbour::events::{EventSink, GameEvent};
+use beggar_my_neighbour::ratscrew::Slappers;
+use beggar_my_neighbour::{print_cards, Game, GameOutcome, GameResult, Player};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue, style};
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+// CONSTANTS
+
+/// How long the computer takes over each card it lays.
+const COMPUTER_DELAY: Duration = Duration::from_millis(700);
+/// Cards shown from the top of the central pile.
+const PILE_SHOWN: usize = 12;
+const COMMENTARY_LINES: usize = 6;
+
+// STRUCTS + METHODS
+
+/// An event sink that keeps the last few things to happen, in words, for
+/// the screen.
+#[derive(Debug)]
+pub struct Commentary {
+    human: Player,
+    lines: VecDeque<String>,
+}
+
+impl Commentary {
+    pub fn new(human: Player) -> Self {
+        Commentary {
+            human,
+            lines: VecDeque::with_capacity(COMMENTARY_LINES),
+        }
+    }
+}
+
+impl EventSink for Commentary {
+    fn emit(&mut self, event: &GameEvent) {
+        let Some(line) = describe_event(event, Some(self.human)) else {
+            return;
+        };
+        if self.lines.len() == COMMENTARY_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Flip,
+    Slap,
+    Quit,
+}
+
+/// Holds the terminal in raw mode on the alternate screen, and puts it
+/// back however play ends.
+pub struct Screen;
+
+impl Screen {
+    pub fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(Screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// HELPER FUNCTIONS
+
+/// Play `game` in the terminal with `human` at the keyboard and `slappers`
+/// in every other seat. Returns the result, or `None` if the human quit.
+pub fn run(
+    mut game: Game<Commentary>,
+    mut slappers: Slappers,
+    human: Player,
+) -> io::Result<Option<GameResult>> {
+    let _screen = Screen::enter()?;
+
+    loop {
+        draw(&game, human)?;
+        if let Some(result) = game.result() {
+            any_key()?;
+            return Ok(Some(result));
+        }
+
+        let human_turn = game.current_player() == human;
+        let deadline = (!human_turn).then(|| Instant::now() + COMPUTER_DELAY);
+        match next_key(deadline)? {
+            Some(Key::Quit) => return Ok(None),
+            Some(Key::Slap) => {
+                game.slap(human);
+                continue;
+            }
+            Some(Key::Flip) if !human_turn => continue,
+            _ => {}
+        }
+
+        game.step();
+
+        // The computer's slap lands after its reaction time unless the human
+        // gets there first.
+        if let Some((player, reaction)) = slappers.react(&game) {
+            draw(&game, human)?;
+            let deadline = Instant::now() + Duration::from_secs_f64(reaction / 1000.0);
+            loop {
+                match next_key(Some(deadline))? {
+                    Some(Key::Flip) => continue,
+                    Some(Key::Slap) => game.slap(human),
+                    Some(Key::Quit) => return Ok(None),
+                    None => game.slap(player),
+                };
+                break;
+            }
+        }
+    }
+}
+
+/// Wait for a key we act on, giving up at `deadline` if there is one.
+fn next_key(deadline: Option<Instant>) -> io::Result<Option<Key>> {
+    loop {
+        let ready = match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Ok(None);
+                }
+                event::poll(remaining)?
+            }
+            None => true,
+        };
+        if !ready {
+            continue;
+        }
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char(' ') | KeyCode::Enter => return Ok(Some(Key::Flip)),
+            KeyCode::Char('s') => return Ok(Some(Key::Slap)),
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(Some(Key::Quit)),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Ok(Some(Key::Quit))
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Put `lines` on the screen from the top, wrapping any too wide for it.
+pub fn show(lines: &[String]) -> io::Result<()> {
+    let width = match terminal::size() {
+        Ok((width, _)) if width > 0 => width as usize,
+        _ => 80,
+    };
+
+    let mut out = io::stdout();
+    queue!(out, terminal::Clear(ClearType::All))?;
+    let mut row = 0;
+    for line in lines {
+        let chars = line.chars().collect::<Vec<_>>();
+        for start in (0..chars.len().max(1)).step_by(width) {
+            let end = (start + width).min(chars.len());
+            let chunk = chars[start.min(end)..end].iter().collect::<String>();
+            queue!(out, cursor::MoveTo(0, row), style::Print(chunk))?;
+            row += 1;
+        }
+    }
+    out.flush()
+}
+
+/// Say what `event` means in a few words, with `human` as "You".
+pub fn describe_event(event: &GameEvent, human: Option<Player>) -> Option<String> {
+    let name = |player| name(player, human);
+    Some(match *event {
+        GameEvent::CardPlayed { player, card } => format!("{} laid {}", name(player), card),
+        GameEvent::PenaltyStarted { penalty, .. } => format!("... which demands {}", penalty),
+        GameEvent::Slapped { player, slap } => {
+            format!("{} slapped the pile: {}!", name(player), slap)
+        }
+        GameEvent::FalseSlap { player, burned } => format!(
+            "{} slapped in error and burned [{}]",
+            name(player),
+            print_cards(burned, " ")
+        ),
+        GameEvent::PileCollected { player, cards } => {
+            format!("{} took the pile of {} cards", name(player), cards.len())
+        }
+        GameEvent::PlayerEliminated { player } => format!("{} ran out of cards", name(player)),
+        GameEvent::GameOver { .. } => return None,
+    })
+}
+
+fn any_key() -> io::Result<()> {
+    loop {
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn draw(game: &Game<Commentary>, human: Player) -> io::Result<()> {
+    let slaps = game.rules().slaps.enabled();
+    let mut lines = vec![
+        if slaps {
+            "EGYPTIAN RATSCREW".to_string()
+        } else {
+            "BEGGAR-MY-NEIGHBOUR".to_string()
+        },
+        String::new(),
+    ];
+
+    for player in Player::all(game.players()) {
+        let status = if !game.is_live(player) {
+            "out".to_string()
+        } else if game.result().is_none() && game.current_player() == player {
+            match game.penalty() {
+                Some((_, owed)) => format!("◀ owes {}", owed),
+                None => "◀ to play".to_string(),
+            }
+        } else {
+            String::new()
+        };
+        lines.push(format!(
+            "{:<10} {:>3} cards  {}",
+            name(player, Some(human)),
+            game.decks()[player.index()].len(),
+            status
+        ));
+    }
+
+    let pile = game.central_pile();
+    let shown = &pile[pile.len().saturating_sub(PILE_SHOWN)..];
+    lines.push(String::new());
+    lines.push(format!(
+        "PILE ({}): {}{}",
+        pile.len(),
+        if shown.len() < pile.len() { "... " } else { "" },
+        print_cards(shown, " ")
+    ));
+    lines.push(match game.penalty() {
+        Some((initiator, owed)) => format!(
+            "PENALTY: {} {} {} more to {}",
+            name(game.current_player(), Some(human)),
+            if game.current_player() == human {
+                "owe"
+            } else {
+                "owes"
+            },
+            owed,
+            name(initiator, Some(human))
+        ),
+        None => "PENALTY: none".to_string(),
+    });
+
+    lines.push(String::new());
+    lines.extend(game.sink().lines.iter().cloned());
+    lines.push(String::new());
+
+    lines.push(match game.result() {
+        Some(result) => format!("{} Press any key.", describe(&result, Some(human))),
+        None if slaps => "[space] flip   [s] slap   [q] quit".to_string(),
+        None => "[space] flip   [q] quit".to_string(),
+    });
+
+    show(&lines)
+}
+
+/// Say how the game ended, with `human` as "You".
+pub fn describe(result: &GameResult, human: Option<Player>) -> String {
+    match result.outcome {
+        GameOutcome::Winner(player) if Some(player) == human => format!(
+            "You win after {} cards and {} tricks!",
+            result.cards_played, result.tricks
+        ),
+        GameOutcome::Winner(player) => format!(
+            "{} wins after {} cards and {} tricks.",
+            name(player, human),
+            result.cards_played,
+            result.tricks
+        ),
+        GameOutcome::Infinite {
+            cycle_start,
+            cycle_length,
+        } => format!(
+            "This game repeats forever: every {} tricks from trick {}.",
+            cycle_length, cycle_start
+        ),
+    }
+}
+
+fn name(player: Player, human: Option<Player>) -> String {
+    if Some(player) == human {
+        return "You".to_string();
+    }
+    format!("Player {}", player.number())
+}
@@ -0,0 +1,240 @@
+// CRATES
+
+use crate::tui::{self, Screen};
+use beggar_my_neighbour::events::{EventSink, GameEvent};
+use beggar_my_neighbour::ratscrew::Slappers;
+use beggar_my_neighbour::{print_cards, Card, Game, GameResult, Player};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use std::io;
+
+// STRUCTS + METHODS
+
+/// The game as it stood after one card was laid or one slap made.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    decks: Vec<Vec<Card>>,
+    live: Vec<bool>,
+    pile: Vec<Card>,
+    current_player: Player,
+    penalty: Option<(Player, u8)>,
+    cards_played: usize,
+    tricks: usize,
+    penalty_phases: usize,
+    /// What happened to reach this frame, in words.
+    notes: Vec<String>,
+    opens_penalty: bool,
+    collects: bool,
+}
+
+impl Frame {
+    fn capture(game: &Game<Notes>) -> Self {
+        Frame {
+            decks: game
+                .decks()
+                .iter()
+                .map(|deck| deck.iter().copied().collect())
+                .collect(),
+            live: Player::all(game.players())
+                .map(|player| game.is_live(player))
+                .collect(),
+            pile: game.central_pile().to_vec(),
+            current_player: game.current_player(),
+            penalty: game.penalty(),
+            cards_played: game.cards_played(),
+            tricks: game.tricks(),
+            penalty_phases: game.penalty_phases(),
+            notes: game.sink().lines.clone(),
+            opens_penalty: game.sink().opens_penalty,
+            collects: game.sink().collects,
+        }
+    }
+}
+
+/// Collects what happens during one step for the frame after it.
+#[derive(Debug, Default)]
+pub struct Notes {
+    lines: Vec<String>,
+    opens_penalty: bool,
+    collects: bool,
+}
+
+impl EventSink for Notes {
+    fn emit(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::PenaltyStarted { .. } => self.opens_penalty = true,
+            GameEvent::PileCollected { .. } | GameEvent::Slapped { .. } => self.collects = true,
+            _ => {}
+        }
+        self.lines.extend(tui::describe_event(event, None));
+    }
+}
+
+/// A finished game, one frame per card, ready to be stepped through.
+#[derive(Debug)]
+pub struct Timeline {
+    frames: Vec<Frame>,
+    result: GameResult,
+}
+
+impl Timeline {
+    /// Play `game` to the end, with `slappers` slapping if the rules allow,
+    /// keeping every position along the way.
+    pub fn record(mut game: Game<Notes>, mut slappers: Slappers) -> Self {
+        let mut frames = vec![Frame::capture(&game)];
+        let mut take = |game: &mut Game<Notes>| {
+            frames.push(Frame::capture(game));
+            *game.sink_mut() = Notes::default();
+        };
+
+        let result = loop {
+            let result = game.step();
+            take(&mut game);
+            if let Some(result) = result {
+                break result;
+            }
+
+            if let Some((player, _)) = slappers.react(&game) {
+                let result = game.slap(player);
+                take(&mut game);
+                if let Some(result) = result {
+                    break result;
+                }
+            }
+        };
+
+        Timeline { frames, result }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Move {
+    Forward,
+    Back,
+    NextPenalty,
+    PreviousPenalty,
+    NextTrick,
+    PreviousTrick,
+    Start,
+    End,
+    Quit,
+}
+
+// HELPER FUNCTIONS
+
+/// Step through `timeline` in the terminal until the viewer quits.
+pub fn run(timeline: &Timeline) -> io::Result<()> {
+    let _screen = Screen::enter()?;
+    let last = timeline.frames.len() - 1;
+    let mut at = 0;
+
+    loop {
+        draw(timeline, at)?;
+
+        let frames = &timeline.frames;
+        at = match next_move()? {
+            Move::Forward => (at + 1).min(last),
+            Move::Back => at.saturating_sub(1),
+            Move::NextPenalty => (at + 1..=last)
+                .find(|&i| frames[i].opens_penalty)
+                .unwrap_or(last),
+            Move::PreviousPenalty => (0..at)
+                .rev()
+                .find(|&i| frames[i].opens_penalty)
+                .unwrap_or(0),
+            Move::NextTrick => (at + 1..=last)
+                .find(|&i| frames[i].collects)
+                .unwrap_or(last),
+            Move::PreviousTrick => (0..at).rev().find(|&i| frames[i].collects).unwrap_or(0),
+            Move::Start => 0,
+            Move::End => last,
+            Move::Quit => return Ok(()),
+        };
+    }
+}
+
+fn next_move() -> io::Result<Move> {
+    loop {
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        return Ok(match key.code {
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') => Move::Forward,
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::Backspace => Move::Back,
+            KeyCode::Char('p') => Move::NextPenalty,
+            KeyCode::Char('P') => Move::PreviousPenalty,
+            KeyCode::Char('t') => Move::NextTrick,
+            KeyCode::Char('T') => Move::PreviousTrick,
+            KeyCode::Home | KeyCode::Char('g') => Move::Start,
+            KeyCode::End | KeyCode::Char('G') => Move::End,
+            KeyCode::Char('q') | KeyCode::Esc => Move::Quit,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Move::Quit,
+            _ => continue,
+        });
+    }
+}
+
+fn draw(timeline: &Timeline, at: usize) -> io::Result<()> {
+    let frame = &timeline.frames[at];
+    let last = at == timeline.frames.len() - 1;
+
+    let mut lines = vec![
+        format!(
+            "STEP {}/{} | {} cards played, {} tricks, {} penalty phases",
+            at,
+            timeline.frames.len() - 1,
+            frame.cards_played,
+            frame.tricks,
+            frame.penalty_phases
+        ),
+        String::new(),
+    ];
+
+    for player in Player::all(frame.decks.len()) {
+        let deck = &frame.decks[player.index()];
+        let status = if !frame.live[player.index()] {
+            " out"
+        } else if !last && frame.current_player == player {
+            " ◀"
+        } else {
+            ""
+        };
+        lines.push(format!(
+            "PLAYER |{}| ({}){}: [{}]",
+            player.number(),
+            deck.len(),
+            status,
+            print_cards(deck, ", ")
+        ));
+    }
+
+    lines.push(String::new());
+    lines.push(format!(
+        "PILE ({}): [{}]",
+        frame.pile.len(),
+        print_cards(&frame.pile, ", ")
+    ));
+    lines.push(match frame.penalty {
+        Some((initiator, owed)) => format!(
+            "PENALTY: player {} owes {} more to player {}",
+            frame.current_player.number(),
+            owed,
+            initiator.number()
+        ),
+        None => "PENALTY: none".to_string(),
+    });
+
+    lines.push(String::new());
+    lines.extend(frame.notes.iter().cloned());
+    if last {
+        lines.push(tui::describe(&timeline.result, None));
+    }
+    lines.push(String::new());
+    lines.push(
+        "[→/←] card   [p/P] penalty phase   [t/T] trick   [g/G] start/end   [q] quit".to_string(),
+    );
+
+    tui::show(&lines)
+}